@@ -0,0 +1,68 @@
+//! Compare iterating a [`Decoder`] with `next` to iterating with the overridden `fold`.
+//!
+//! Run with `cargo bench`.
+use gc_adpcm::{Decoder, Dsp};
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+/// The amount of frames that are decoded per iteration
+const FRAMES: u32 = 100_000;
+/// The amount of times every benchmark is run
+const ITERATIONS: u32 = 20;
+
+/// Create a stereo decoder for pseudo-random frames.
+fn decoder(data: &[u8]) -> Decoder<Cursor<&[u8]>, gc_adpcm::StereoInterleaved> {
+    let coefficients = [
+        492, -294, 2389, -1166, 1464, -354, 3275, -1614, 1016, -51, 3654, -1765, 672, -93, 2837,
+        -1024,
+    ];
+    Decoder::interleaved_stereo(
+        Cursor::new(data),
+        Dsp::from_coefficients(coefficients),
+        Dsp::from_coefficients(coefficients),
+        FRAMES,
+    )
+}
+
+/// Run `f` [`ITERATIONS`] times and return the fastest run and the result of the last run.
+fn bench(f: impl Fn() -> i64) -> (Duration, i64) {
+    let mut fastest = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        result = black_box(f());
+        fastest = fastest.min(start.elapsed());
+    }
+    (fastest, result)
+}
+
+fn main() {
+    let mut seed = 0x1234_5678_u32;
+    let data: Vec<u8> = (0..FRAMES * 16)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed.to_le_bytes()[0]
+        })
+        .collect();
+
+    let (next, next_sum) = bench(|| {
+        let mut sum = 0;
+        // A for loop calls `next` for every sample
+        for sample in decoder(&data) {
+            sum += i64::from(sample.unwrap());
+        }
+        sum
+    });
+    let (fold, fold_sum) =
+        bench(|| decoder(&data).fold(0, |sum, sample| sum + i64::from(sample.unwrap())));
+    assert_eq!(
+        next_sum, fold_sum,
+        "Both methods should decode the same samples"
+    );
+
+    println!("next: {next:?}");
+    println!("fold: {fold:?}");
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+use gc_adpcm::{Dsp, FRAME_SIZE};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: [u8; FRAME_SIZE + 36]| {
+    let (frame, state) = data.split_at(FRAME_SIZE);
+    let mut coefficients = [0; 16];
+    for (coefficient, bytes) in coefficients.iter_mut().zip(state.chunks_exact(2)) {
+        *coefficient = i16::from_le_bytes([bytes[0], bytes[1]]);
+    }
+    let hist1 = i16::from_le_bytes([state[32], state[33]]);
+    let hist2 = i16::from_le_bytes([state[34], state[35]]);
+
+    let mut dsp = Dsp::new(coefficients, hist1, hist2);
+    dsp.decode_frame(frame.try_into().unwrap());
+});
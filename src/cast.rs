@@ -0,0 +1,47 @@
+//! View decoded samples as bytes using `bytemuck`.
+use std::borrow::Cow;
+
+/// View `samples` as bytes without copying, in the native endianness of the platform.
+///
+/// Every sample is two bytes, in the same order as the samples. Use
+/// [`samples_as_le_bytes`] if the bytes need to be little-endian, for example for a WAV
+/// file.
+///
+/// ```
+/// use gc_adpcm::samples_as_bytes;
+///
+/// let samples = [0x0102, -2];
+/// let bytes = samples_as_bytes(&samples);
+/// assert_eq!(bytes.len(), 4);
+/// assert_eq!(bytes[..2], 0x0102_i16.to_ne_bytes());
+/// ```
+#[must_use]
+pub fn samples_as_bytes(samples: &[i16]) -> &[u8] {
+    bytemuck::cast_slice(samples)
+}
+
+/// View `samples` as little-endian bytes.
+///
+/// The bytes are always little-endian, on every platform. On little-endian platforms the
+/// bytes are borrowed without copying, on big-endian platforms a copy with the bytes of
+/// every sample swapped is returned.
+///
+/// ```
+/// use gc_adpcm::samples_as_le_bytes;
+///
+/// let samples = [0x0102, -2];
+/// assert_eq!(*samples_as_le_bytes(&samples), [0x02, 0x01, 0xFE, 0xFF]);
+/// ```
+#[must_use]
+pub fn samples_as_le_bytes(samples: &[i16]) -> Cow<'_, [u8]> {
+    if cfg!(target_endian = "big") {
+        Cow::Owned(
+            samples
+                .iter()
+                .flat_map(|sample| sample.to_le_bytes())
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(bytemuck::cast_slice(samples))
+    }
+}
@@ -1,38 +1,130 @@
 //! An easy-to-use decoder that takes a `std::io::Read` and outputs `i16` as an iterator.
-use crate::{Dsp, SAMPLES_PER_FRAME};
-use std::io::Read;
+use crate::util::clamp;
+use crate::{
+    frames_from_bytes, samples_from_nibbles, Dsp, DspHeader, FRAME_SIZE, SAMPLES_PER_FRAME,
+};
+use buffer::FrameBuffer;
+use std::collections::VecDeque;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
 
+mod buffer;
+mod builder;
+mod looping;
+mod planar;
+mod push;
+mod split;
+mod stats;
+
+#[cfg(feature = "tokio")]
+mod async_decoder;
+
+pub use builder::*;
+pub use looping::*;
+pub use planar::*;
+pub use push::*;
+pub use split::*;
+pub use stats::*;
+
+#[cfg(feature = "tokio")]
+pub use async_decoder::*;
+
 /// Private module to prevent users from implementing [`Channels`] for other types.
 mod private {
-    use crate::{Mono, Stereo, StereoInterleaved};
+    use crate::{Channels, Decoder, Mono, Stereo, StereoInterleaved};
+    use std::io::Read;
 
     /// Sealed trait to prevent users from implementing [`Channels`] for other types.
-    pub trait Sealed {}
-    impl Sealed for Mono {}
-    impl Sealed for Stereo {}
-    impl Sealed for StereoInterleaved {}
+    pub trait Sealed {
+        /// Decode the next frame(s) into the buffer of the decoder if it is empty.
+        fn fill_buffer<R: Read>(decoder: &mut Decoder<R, Self>) -> Result<(), std::io::Error>
+        where
+            Self: Channels + Sized;
+    }
+    impl Sealed for Mono {
+        fn fill_buffer<R: Read>(decoder: &mut Decoder<R, Self>) -> Result<(), std::io::Error> {
+            decoder.fill_buffer()
+        }
+    }
+    impl Sealed for Stereo {
+        fn fill_buffer<R: Read>(decoder: &mut Decoder<R, Self>) -> Result<(), std::io::Error> {
+            decoder.fill_buffer()
+        }
+    }
+    impl Sealed for StereoInterleaved {
+        fn fill_buffer<R: Read>(decoder: &mut Decoder<R, Self>) -> Result<(), std::io::Error> {
+            decoder.fill_buffer()
+        }
+    }
 }
 
 /// Sealed trait for encoding the channel layout in the type system.
-pub trait Channels: private::Sealed {}
+///
+/// The constants can be used to write code that is generic over the channel layout.
+///
+/// ```
+/// use gc_adpcm::{Channels, Mono, Stereo, StereoInterleaved};
+///
+/// fn describe<C: Channels>() -> (usize, u32) {
+///     (C::CHANNEL_COUNT, C::FRAMES_PER_STEP)
+/// }
+/// assert_eq!(describe::<Mono>(), (1, 1));
+/// assert_eq!(describe::<Stereo>(), (2, 1));
+/// assert_eq!(describe::<StereoInterleaved>(), (2, 2));
+/// ```
+pub trait Channels: private::Sealed {
+    /// The amount of channels in the output
+    const CHANNEL_COUNT: usize;
+    /// The amount of frames that are read from one reader for every step of decoding
+    const FRAMES_PER_STEP: u32;
+}
 
 /// There is only one channel.
 pub enum Mono {}
-impl Channels for Mono {}
+impl Channels for Mono {
+    const CHANNEL_COUNT: usize = 1;
+    const FRAMES_PER_STEP: u32 = 1;
+}
 
 /// There are two channels in two separate streams.
 pub enum Stereo {}
-impl Channels for Stereo {}
+impl Channels for Stereo {
+    const CHANNEL_COUNT: usize = 2;
+    const FRAMES_PER_STEP: u32 = 1;
+}
 
 /// There are two channels interleaved per frame in one stream.
 pub enum StereoInterleaved {}
-impl Channels for StereoInterleaved {}
+impl Channels for StereoInterleaved {
+    const CHANNEL_COUNT: usize = 2;
+    const FRAMES_PER_STEP: u32 = 2;
+}
 
 /// Wrapper around [`Dsp`] that handles channel layout.
 ///
 /// It takes the initial DSP state and one or two readers for the stream data and
 /// outputs a `Result<i16, std::io::Error>` iterator.
+///
+/// When all frames are decoded, the iterator will keep returning `None`.
+///
+/// ```
+/// use gc_adpcm::{Decoder, Dsp};
+/// use std::io::Cursor;
+///
+/// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+/// let mut decoder = Decoder::mono(Cursor::new(frame), Dsp::default(), 1);
+/// assert_eq!(decoder.by_ref().count(), 14);
+/// for _ in 0..3 {
+///     assert!(decoder.next().is_none());
+/// }
+/// ```
+///
+/// A decoder without any frames or samples never reads from the reader(s).
+///
+/// If reading fails, all samples of the frames that were completely decoded are returned
+/// first. Then the error is returned once, after which the iterator returns `None`.
+/// A frame that failed to read is never partially returned.
 pub struct Decoder<R: Read, C: Channels> {
     /// The reader for the left/mono/interleaved audio stream
     left_reader: R,
@@ -42,42 +134,494 @@ pub struct Decoder<R: Read, C: Channels> {
     left_state: Dsp,
     /// The DSP state of the right channel, not available when channel is [`Mono`]
     right_state: Option<Dsp>,
+    /// The history of the left/mono channel at the start of the stream
+    initial_history: (i16, i16),
+    /// The gain applied to the left/mono channel
+    left_gain: Option<f32>,
+    /// The gain applied to the right channel, not available when channel is [`Mono`]
+    right_gain: Option<f32>,
+    /// The amount of frames that still need to be decoded
+    frames_remaing: u32,
+    /// The amount of frames that needed to be decoded when the decoder was created
+    frames_total: u32,
+    /// The amount of samples in the output, the last frame can contain more samples
+    samples_total: u64,
+    /// The channel to decode when decoding one channel of an interleaved stream as [`Mono`]
+    interleaved_channel: Option<Channel>,
+    /// Buffer for the decoded frame(s)
+    buffer: FrameBuffer,
+    /// Statistics about the decoded frames, only collected if enabled
+    stats: Option<DecodeStats>,
+    /// The group of frames that is read at once, only used for [`StereoInterleaved`]
+    /// with more than one frame per group
+    group: Option<FrameGroup>,
+    /// The index in one channel of the last frame that was read or failed to read
+    last_frame_index: Option<u32>,
+    /// The amount of bytes read from the left/mono/interleaved reader
+    left_bytes_consumed: u64,
+    /// The amount of bytes read from the right reader, only used for [`Stereo`]
+    right_bytes_consumed: u64,
+    /// The readers and amount of frames of the segments that are decoded after the current
+    /// reader, only used for [`Mono`]
+    segments: VecDeque<(R, u32)>,
+    /// The total amount of frames in `segments`
+    segment_frames: u32,
+    /// If segments were added with [`Decoder::chain`], even if they are already decoded
+    chained: bool,
+    /// Callback that is called for every decoded frame
+    on_frame: Option<FrameCallback>,
+    /// If an error was returned, after which no more samples are returned
+    failed: bool,
+    /// Fake field for the [`Channels`] typestate
+    _phantom_data: PhantomData<C>,
+}
+
+/// Callback for [`Decoder::on_frame`].
+type FrameCallback = Box<dyn FnMut(u32, &[i16]) + Send>;
+
+/// Error returned by [`Decoder::validate_length`].
+#[derive(Debug)]
+pub enum LengthError {
+    /// Getting the length of the reader failed
+    Io(std::io::Error),
+    /// The length of the reader does not match the amount of frames
+    Mismatch(LengthMismatch),
+}
+
+impl std::fmt::Display for LengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to get the length of the reader: {e}"),
+            Self::Mismatch(mismatch) => mismatch.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LengthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Mismatch(mismatch) => Some(mismatch),
+        }
+    }
+}
+
+impl From<std::io::Error> for LengthError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The remaining length of a reader does not match the amount of remaining frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthMismatch {
+    /// The amount of bytes needed for the remaining frames
+    pub expected: u64,
+    /// The amount of bytes that are left in the reader
+    pub actual: u64,
+}
+
+impl LengthMismatch {
+    /// The amount of bytes the reader has more than expected.
+    ///
+    /// This is negative if the reader is too short.
+    #[must_use]
+    pub fn difference(&self) -> i128 {
+        i128::from(self.actual) - i128::from(self.expected)
+    }
+}
+
+impl std::fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} bytes but the reader has {} bytes left",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for LengthMismatch {}
+
+/// Error returned by [`Decoder::read_planar`](Decoder<R, Stereo>::read_planar).
+///
+/// The samples before the error are already written to the buffers.
+#[derive(Debug)]
+pub struct ReadPlanarError {
+    /// The amount of samples per channel that were written before the error
+    pub written: usize,
+    /// The error of the reader
+    pub error: std::io::Error,
+}
+
+impl std::fmt::Display for ReadPlanarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to decode after {} samples per channel: {}",
+            self.written, self.error
+        )
+    }
+}
+
+impl std::error::Error for ReadPlanarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<ReadPlanarError> for std::io::Error {
+    fn from(e: ReadPlanarError) -> Self {
+        e.error
+    }
+}
+
+/// Error returned by the `try_*` constructors of [`Decoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstructError {
+    /// The amount of frames of all channels does not fit in a [`u32`]
+    TooManyFrames,
+    /// The size in bytes of the frames does not fit in a [`usize`]
+    TooManyBytes,
+}
+
+impl std::fmt::Display for ConstructError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyFrames => write!(f, "the amount of frames does not fit in a u32"),
+            Self::TooManyBytes => write!(f, "the size of the frames does not fit in a usize"),
+        }
+    }
+}
+
+impl std::error::Error for ConstructError {}
+
+/// A snapshot of the state of a [`Decoder`], created by [`Decoder::snapshot`].
+pub struct DecoderState<C: Channels> {
+    /// The position of the left/mono/interleaved reader
+    left_position: u64,
+    /// The position of the right reader, only available on [`Stereo`]
+    right_position: Option<u64>,
+    /// The DSP state of the left/mono channel
+    left_state: Dsp,
+    /// The DSP state of the right channel, not available when channel is [`Mono`]
+    right_state: Option<Dsp>,
     /// The amount of frames that still need to be decoded
     frames_remaing: u32,
     /// Buffer for the decoded frame(s)
-    buffer: Vec<i16>,
+    buffer: FrameBuffer,
+    /// The group of frames that is read at once
+    group: Option<FrameGroup>,
+    /// The amount of bytes read from the left/mono/interleaved reader
+    left_bytes_consumed: u64,
+    /// The amount of bytes read from the right reader
+    right_bytes_consumed: u64,
     /// Fake field for the [`Channels`] typestate
     _phantom_data: PhantomData<C>,
 }
 
+// Not derived, as that would require `C: Clone`
+impl<C: Channels> Clone for DecoderState<C> {
+    fn clone(&self) -> Self {
+        Self {
+            left_position: self.left_position,
+            right_position: self.right_position,
+            left_state: self.left_state,
+            right_state: self.right_state,
+            frames_remaing: self.frames_remaing,
+            buffer: self.buffer.clone(),
+            group: self.group.clone(),
+            left_bytes_consumed: self.left_bytes_consumed,
+            right_bytes_consumed: self.right_bytes_consumed,
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// A channel of a stereo audio stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// The left channel
+    Left,
+    /// The right channel
+    Right,
+}
+
+/// A group of frames of a stereo audio stream, with the left frames before the right frames.
+#[derive(Clone)]
+struct FrameGroup {
+    /// The amount of frames per channel in a full group
+    frames_per_group: u32,
+    /// The frames of the current group
+    data: Vec<u8>,
+    /// The amount of frames per channel in the current group
+    len: usize,
+    /// The index of the next frame in the current group
+    next: usize,
+}
+
 impl<R: Read> Decoder<R, Mono> {
     /// Decode a mono audio stream.
     ///
     /// `frames` is the amount of frames in the channel.
+    ///
+    /// The reader can also be borrowed, as `&mut R` implements [`Read`]. The decoder only
+    /// reads the frames it decodes, so the reader can be used again after the decoder is
+    /// dropped. This works for all constructors.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::{Cursor, Read};
+    ///
+    /// let mut data = Cursor::new([0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xAB, 0xCD]);
+    /// let decoder = Decoder::mono(&mut data, Dsp::default(), 1);
+    /// assert_eq!(decoder.count(), 14);
+    /// let mut rest = Vec::new();
+    /// data.read_to_end(&mut rest)?;
+    /// assert_eq!(rest, [0xAB, 0xCD]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
     pub fn mono(reader: R, state: Dsp, frames: u32) -> Self {
         Self {
             left_reader: reader,
             right_reader: None,
             left_state: state,
             right_state: None,
+            initial_history: (state.hist1, state.hist2),
+            left_gain: None,
+            right_gain: None,
             frames_remaing: frames,
-            buffer: Vec::with_capacity(14),
+            frames_total: frames,
+            samples_total: u64::from(frames) * 14,
+            interleaved_channel: None,
+            buffer: FrameBuffer::new(),
+            stats: None,
+            group: None,
+            last_frame_index: None,
+            left_bytes_consumed: 0,
+            right_bytes_consumed: 0,
+            segments: VecDeque::new(),
+            segment_frames: 0,
+            chained: false,
+            on_frame: None,
+            failed: false,
             _phantom_data: PhantomData,
         }
     }
 
+    /// Decode a mono audio stream, checking that `frames` is supported.
+    ///
+    /// The size in bytes of all frames needs to fit in a [`usize`], so on 32-bit platforms
+    /// at most `usize::MAX / 8` frames are supported.
+    ///
+    /// # Errors
+    /// Returns an error if there are too many frames.
+    pub fn try_mono(reader: R, state: Dsp, frames: u32) -> Result<Self, ConstructError> {
+        check_frames(frames, 1)?;
+        Ok(Self::mono(reader, state, frames))
+    }
+
     /// Decode a mono audio stream.
     ///
     /// `samples` is the amount of samples in the channel.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let decoder = Decoder::mono_samples(Cursor::new(&frames), Dsp::default(), 20);
+    /// assert_eq!(decoder.count(), 20);
+    /// ```
     pub fn mono_samples(reader: R, state: Dsp, samples: u32) -> Self {
-        Self {
-            left_reader: reader,
-            right_reader: None,
-            left_state: state,
-            right_state: None,
-            frames_remaing: samples.div_ceil(SAMPLES_PER_FRAME),
-            buffer: Vec::with_capacity(14),
-            _phantom_data: PhantomData,
+        let mut decoder = Self::mono(reader, state, samples.div_ceil(SAMPLES_PER_FRAME));
+        decoder.samples_total = u64::from(samples);
+        decoder
+    }
+
+    /// Decode a mono audio stream.
+    ///
+    /// `nibbles` is the amount of nibbles in the channel, including the headers of the
+    /// frames, as stored in the header of a DSP file. See [`samples_from_nibbles`] for
+    /// how it is converted to the amount of samples.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let decoder = Decoder::mono_nibbles(Cursor::new(&frames), Dsp::default(), 21);
+    /// assert_eq!(decoder.count(), 17);
+    /// ```
+    pub fn mono_nibbles(reader: R, state: Dsp, nibbles: u32) -> Self {
+        Self::mono_samples(reader, state, samples_from_nibbles(nibbles))
+    }
+
+    /// Decode a DSP file, which starts with a [`DspHeader`].
+    ///
+    /// The header is read and validated, and the DSP state and amount of samples are taken
+    /// from it. Use [`DspHeader::parse`] to get other information, like the sample rate.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp, DspHeader};
+    /// use std::io::Cursor;
+    ///
+    /// let header = DspHeader {
+    ///     sample_count: 20,
+    ///     nibble_count: 26,
+    ///     sample_rate: 32_000,
+    ///     looping: false,
+    ///     format: 0,
+    ///     loop_start: 2,
+    ///     loop_end: 25,
+    ///     current_address: 2,
+    ///     coefficients: [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ///     gain: 0,
+    ///     predictor_scale: 0x03,
+    ///     hist1: 0,
+    ///     hist2: 0,
+    ///     loop_predictor_scale: 0,
+    ///     loop_hist1: 0,
+    ///     loop_hist2: 0,
+    /// };
+    /// let data = [0x03, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let file = [&header.to_bytes()[..], &data].concat();
+    ///
+    /// let decoder = Decoder::from_dsp_file(Cursor::new(file))?;
+    /// let expected = Decoder::mono_samples(Cursor::new(data), header.dsp(), 20);
+    /// assert!(decoder.map(Result::unwrap).eq(expected.map(Result::unwrap)));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if reading the header fails, or an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] with a [`HeaderError`](crate::HeaderError) if the header is not valid.
+    pub fn from_dsp_file(mut reader: R) -> Result<Self, std::io::Error> {
+        let mut bytes = [0; DspHeader::SIZE];
+        reader.read_exact(&mut bytes)?;
+        let header = DspHeader::parse(&bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::mono_samples(
+            reader,
+            header.dsp(),
+            header.sample_count,
+        ))
+    }
+
+    /// Decode the samples `start..end` of a mono audio stream.
+    ///
+    /// The stream is decoded from the beginning and the samples before `start` are
+    /// discarded, so the history is exact. Use [`Decoder::mono_range_seek`] to skip the
+    /// frames before `start` without decoding them. [`Decoder::position`] includes the
+    /// samples before `start`.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(3);
+    /// let all: Vec<i16> = Decoder::mono(Cursor::new(&frames), Dsp::default(), 3).collect::<Result<_, _>>()?;
+    /// let range = Decoder::mono_range(Cursor::new(&frames), Dsp::default(), 10, 30)?;
+    /// assert_eq!(range.collect::<Result<Vec<_>, _>>()?, all[10..30]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if `start` is larger than `end`, if `end` does not fit in a [`u32`],
+    /// or if reading the samples before `start` fails.
+    pub fn mono_range(reader: R, state: Dsp, start: u64, end: u64) -> Result<Self, std::io::Error> {
+        let mut decoder = Self::mono_samples(reader, state, range_end(start, end)?);
+        let frames = start / u64::from(SAMPLES_PER_FRAME);
+        decoder.skip_frames(u32::try_from(frames).unwrap_or_else(|_| unreachable!()))?;
+        decoder.skip_samples(start % u64::from(SAMPLES_PER_FRAME))?;
+        Ok(decoder)
+    }
+
+    /// Discard the next `samples` samples.
+    fn skip_samples(&mut self, samples: u64) -> Result<(), std::io::Error> {
+        for _ in 0..samples {
+            self.next().transpose()?;
+        }
+        Ok(())
+    }
+
+    /// Decode one channel of a stereo audio stream interleaved per frame.
+    ///
+    /// The frames of the other channel are skipped without decoding them, as the channels
+    /// do not depend on each other. `state` is the DSP state of `channel`.
+    ///
+    /// `channel_frames` is the amount of frames in *one* channel.
+    pub fn mono_from_interleaved(
+        reader: R,
+        state: Dsp,
+        channel: Channel,
+        channel_frames: u32,
+    ) -> Self {
+        let mut decoder = Self::mono(reader, state, channel_frames);
+        decoder.interleaved_channel = Some(channel);
+        decoder
+    }
+
+    /// Multiply every decoded sample by `gain`.
+    ///
+    /// The result is clamped to the range of an [`i16`]. A gain of `1.0` does not change the samples.
+    #[must_use]
+    pub const fn with_gain(mut self, gain: f32) -> Self {
+        self.left_gain = Some(gain);
+        self
+    }
+
+    /// Continue decoding `next_frames` frames from `next_reader` after the current audio
+    /// stream ends.
+    ///
+    /// The history is not reset between the segments, so segments that are split from one
+    /// audio stream decode exactly like the original stream. This can be chained multiple
+    /// times. All samples of the frames of a segment are decoded, so if the decoder was
+    /// created with an amount of samples, the padding of its last frame is decoded too.
+    ///
+    /// [`Decoder::snapshot`] and [`Decoder::restore`] only work within one segment. This method
+    /// shadows [`Iterator::chain`], use `Iterator::chain(decoder, other)` for that instead.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let coefficients = [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(3);
+    /// let whole = Decoder::mono(Cursor::new(&frames[..]), Dsp::from_coefficients(coefficients), 3);
+    /// let segments = Decoder::mono(Cursor::new(&frames[..8]), Dsp::from_coefficients(coefficients), 1)
+    ///     .chain(Cursor::new(&frames[8..]), 2);
+    /// assert!(whole.map(Result::unwrap).eq(segments.map(Result::unwrap)));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the total amount of frames does not fit in a [`u32`].
+    #[must_use]
+    pub fn chain(mut self, next_reader: R, next_frames: u32) -> Self {
+        let overflow = || panic!("The total amount of frames does not fit in a u32");
+        self.frames_remaing = self
+            .frames_remaing
+            .checked_add(next_frames)
+            .unwrap_or_else(overflow);
+        self.frames_total = self
+            .frames_total
+            .checked_add(next_frames)
+            .unwrap_or_else(overflow);
+        self.samples_total = u64::from(self.frames_total) * u64::from(SAMPLES_PER_FRAME);
+        self.segment_frames += next_frames;
+        self.segments.push_back((next_reader, next_frames));
+        self.chained = true;
+        self
+    }
+
+    /// Switch to the reader of the next segment if all frames of the current reader are read.
+    fn next_segment(&mut self) {
+        while self.frames_remaing == self.segment_frames {
+            let Some((reader, frames)) = self.segments.pop_front() else {
+                break;
+            };
+            self.left_reader = reader;
+            self.segment_frames -= frames;
         }
     }
 }
@@ -86,6 +630,17 @@ impl<R: Read> Decoder<R, Stereo> {
     /// Decode a stereo audio stream where each channel has their own buffer.
     ///
     /// `channel_frames` is the amount of frames in *one* channel.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11]);
+    /// let right = Cursor::new([0x00, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22]);
+    /// let decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 1);
+    /// assert_eq!(decoder.collect::<Result<Vec<_>, _>>()?, [1, 2].repeat(14));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
     pub fn stereo(
         left_reader: R,
         left_state: Dsp,
@@ -98,15 +653,66 @@ impl<R: Read> Decoder<R, Stereo> {
             right_reader: Some(right_reader),
             left_state,
             right_state: Some(right_state),
+            initial_history: (left_state.hist1, left_state.hist2),
+            left_gain: None,
+            right_gain: None,
             frames_remaing: channel_frames,
-            buffer: Vec::with_capacity(28),
+            frames_total: channel_frames,
+            samples_total: u64::from(channel_frames) * 28,
+            interleaved_channel: None,
+            buffer: FrameBuffer::new(),
+            stats: None,
+            group: None,
+            last_frame_index: None,
+            left_bytes_consumed: 0,
+            right_bytes_consumed: 0,
+            segments: VecDeque::new(),
+            segment_frames: 0,
+            chained: false,
+            on_frame: None,
+            failed: false,
             _phantom_data: PhantomData,
         }
     }
 
+    /// Decode a stereo audio stream where each channel has their own buffer, checking that
+    /// `channel_frames` is supported.
+    ///
+    /// The size in bytes of all frames of one channel needs to fit in a [`usize`], so on
+    /// 32-bit platforms at most `usize::MAX / 8` frames are supported.
+    ///
+    /// # Errors
+    /// Returns an error if there are too many frames.
+    pub fn try_stereo(
+        left_reader: R,
+        left_state: Dsp,
+        right_reader: R,
+        right_state: Dsp,
+        channel_frames: u32,
+    ) -> Result<Self, ConstructError> {
+        check_frames(channel_frames, 1)?;
+        Ok(Self::stereo(
+            left_reader,
+            left_state,
+            right_reader,
+            right_state,
+            channel_frames,
+        ))
+    }
+
     /// Decode a stereo audio stream where each channel has their own buffer.
     ///
     /// `channel_samples` is the amount of samples in *one* channel.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let (left, right) = (Cursor::new(&frames), Cursor::new(&frames));
+    /// let decoder = Decoder::stereo_samples(left, Dsp::default(), right, Dsp::default(), 20);
+    /// assert_eq!(decoder.pairs().count(), 20);
+    /// ```
     pub fn stereo_samples(
         left_reader: R,
         left_state: Dsp,
@@ -114,15 +720,61 @@ impl<R: Read> Decoder<R, Stereo> {
         right_state: Dsp,
         channel_samples: u32,
     ) -> Self {
-        Self {
+        let mut decoder = Self::stereo(
             left_reader,
-            right_reader: Some(right_reader),
             left_state,
-            right_state: Some(right_state),
-            frames_remaing: channel_samples.div_ceil(SAMPLES_PER_FRAME),
-            buffer: Vec::with_capacity(28),
-            _phantom_data: PhantomData,
-        }
+            right_reader,
+            right_state,
+            channel_samples.div_ceil(SAMPLES_PER_FRAME),
+        );
+        decoder.samples_total = u64::from(channel_samples) * 2;
+        decoder
+    }
+
+    /// Decode a stereo audio stream where each channel has their own buffer.
+    ///
+    /// `channel_nibbles` is the amount of nibbles in *one* channel, including the headers
+    /// of the frames. See [`samples_from_nibbles`] for how it is converted to the amount
+    /// of samples.
+    pub fn stereo_nibbles(
+        left_reader: R,
+        left_state: Dsp,
+        right_reader: R,
+        right_state: Dsp,
+        channel_nibbles: u32,
+    ) -> Self {
+        Self::stereo_samples(
+            left_reader,
+            left_state,
+            right_reader,
+            right_state,
+            samples_from_nibbles(channel_nibbles),
+        )
+    }
+
+    /// The amount of bytes that are read from the left and right reader.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let mut decoder = Decoder::stereo(Cursor::new([0; 16]), Dsp::default(), Cursor::new([0; 16]), Dsp::default(), 2);
+    /// decoder.next();
+    /// assert_eq!(decoder.bytes_consumed_per_reader(), (8, 8));
+    /// ```
+    #[must_use]
+    pub const fn bytes_consumed_per_reader(&self) -> (u64, u64) {
+        (self.left_bytes_consumed, self.right_bytes_consumed)
+    }
+
+    /// Multiply every decoded sample of the left and right channel by `left_gain` and `right_gain`.
+    ///
+    /// The result is clamped to the range of an [`i16`]. A gain of `1.0` does not change the samples.
+    #[must_use]
+    pub const fn with_gain(mut self, left_gain: f32, right_gain: f32) -> Self {
+        self.left_gain = Some(left_gain);
+        self.right_gain = Some(right_gain);
+        self
     }
 }
 
@@ -130,6 +782,21 @@ impl<R: Read> Decoder<R, StereoInterleaved> {
     /// Decode a stereo audio stream interleaved per frame.
     ///
     /// `channel_frames` is the amount of frames in *one* channel.
+    ///
+    /// The output is interleaved per sample, starting with the left channel. This is the
+    /// same for all stereo decoders.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = [0x00, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11];
+    /// let right = [0x00, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22];
+    /// let data = [left, right].concat();
+    /// let decoder = Decoder::interleaved_stereo(Cursor::new(&data), Dsp::default(), Dsp::default(), 1);
+    /// assert_eq!(decoder.collect::<Result<Vec<_>, _>>()?, [1, 2].repeat(14));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
     pub fn interleaved_stereo(
         reader: R,
         left_state: Dsp,
@@ -141,121 +808,1953 @@ impl<R: Read> Decoder<R, StereoInterleaved> {
             right_reader: None,
             left_state,
             right_state: Some(right_state),
+            initial_history: (left_state.hist1, left_state.hist2),
+            left_gain: None,
+            right_gain: None,
             frames_remaing: channel_frames * 2,
-            buffer: Vec::with_capacity(28),
+            frames_total: channel_frames * 2,
+            samples_total: u64::from(channel_frames) * 28,
+            interleaved_channel: None,
+            buffer: FrameBuffer::new(),
+            stats: None,
+            group: None,
+            last_frame_index: None,
+            left_bytes_consumed: 0,
+            right_bytes_consumed: 0,
+            segments: VecDeque::new(),
+            segment_frames: 0,
+            chained: false,
+            on_frame: None,
+            failed: false,
             _phantom_data: PhantomData,
         }
     }
 
+    /// Decode a stereo audio stream interleaved per `frames_per_group` frames.
+    ///
+    /// Every group contains `frames_per_group` frames of the left channel, followed by the
+    /// same amount of frames of the right channel. The last group can be shorter, in which
+    /// case it contains the remaining frames of the left channel followed by the remaining
+    /// frames of the right channel. A `frames_per_group` of `0` or `1` is the same as
+    /// [`Decoder::interleaved_stereo`].
+    ///
+    /// `channel_frames` is the amount of frames in *one* channel.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let (left, right) = ([0x00, 0x11, 0, 0, 0, 0, 0, 0], [0x00, 0x22, 0, 0, 0, 0, 0, 0]);
+    /// // Two groups of two frames, and a short group of one frame
+    /// let data = [left, left, right, right, left, left, right, right, left, right].concat();
+    /// let decoder = Decoder::interleaved_stereo_grouped(Cursor::new(data), Dsp::default(), Dsp::default(), 5, 2);
+    /// let pairs = decoder.pairs().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(pairs.len(), 5 * 14);
+    /// assert!(pairs.chunks(14).all(|frame| frame[..2] == [(1, 2), (1, 2)]));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn interleaved_stereo_grouped(
+        reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+        channel_frames: u32,
+        frames_per_group: u32,
+    ) -> Self {
+        let mut decoder = Self::interleaved_stereo(reader, left_state, right_state, channel_frames);
+        if frames_per_group > 1 {
+            decoder.group = Some(FrameGroup {
+                frames_per_group,
+                data: Vec::new(),
+                len: 0,
+                next: 0,
+            });
+        }
+        decoder
+    }
+
+    /// Decode a stereo audio stream interleaved per frame, checking that `channel_frames`
+    /// is supported.
+    ///
+    /// The frames of both channels are counted together, so at most `u32::MAX / 2` frames
+    /// per channel are supported. The size in bytes of the frames of both channels also
+    /// needs to fit in a [`usize`].
+    ///
+    /// ```
+    /// use gc_adpcm::{ConstructError, Decoder, Dsp};
+    /// use std::io::empty;
+    ///
+    /// let state = Dsp::default();
+    /// let result = Decoder::try_interleaved_stereo(empty(), state, state, u32::MAX);
+    /// assert!(matches!(result, Err(ConstructError::TooManyFrames)));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if there are too many frames.
+    pub fn try_interleaved_stereo(
+        reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+        channel_frames: u32,
+    ) -> Result<Self, ConstructError> {
+        check_frames(channel_frames, 2)?;
+        Ok(Self::interleaved_stereo(
+            reader,
+            left_state,
+            right_state,
+            channel_frames,
+        ))
+    }
+
     /// Decode a stereo audio stream interleaved per frame.
     ///
     /// `channel_samples` is the amount of samples in *one* channel.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(4);
+    /// let decoder = Decoder::interleaved_stereo_samples(Cursor::new(&frames), Dsp::default(), Dsp::default(), 20);
+    /// assert_eq!(decoder.pairs().count(), 20);
+    /// ```
     pub fn interleaved_stereo_samples(
         reader: R,
         left_state: Dsp,
         right_state: Dsp,
         channel_samples: u32,
     ) -> Self {
-        Self {
-            left_reader: reader,
-            right_reader: None,
+        let mut decoder = Self::interleaved_stereo(
+            reader,
             left_state,
-            right_state: Some(right_state),
-            frames_remaing: channel_samples.div_ceil(SAMPLES_PER_FRAME) * 2,
-            buffer: Vec::with_capacity(28),
-            _phantom_data: PhantomData,
-        }
+            right_state,
+            channel_samples.div_ceil(SAMPLES_PER_FRAME),
+        );
+        decoder.samples_total = u64::from(channel_samples) * 2;
+        decoder
     }
-}
 
-impl<R: Read> Iterator for Decoder<R, Mono> {
-    type Item = Result<i16, std::io::Error>;
+    /// Decode a stereo audio stream interleaved per frame.
+    ///
+    /// `channel_nibbles` is the amount of nibbles in *one* channel, including the headers
+    /// of the frames. See [`samples_from_nibbles`] for how it is converted to the amount
+    /// of samples.
+    pub fn interleaved_stereo_nibbles(
+        reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+        channel_nibbles: u32,
+    ) -> Self {
+        Self::interleaved_stereo_samples(
+            reader,
+            left_state,
+            right_state,
+            samples_from_nibbles(channel_nibbles),
+        )
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.buffer.is_empty() && self.frames_remaing != 0 {
-            let mut frame = [0; 8];
-            let result = self.left_reader.read_exact(&mut frame);
-            if let Err(e) = result {
-                return Some(Err(e));
-            };
-            let mut samples = self.left_state.decode_frame(frame);
-            // Reverse the samples as they are output in the wrong order
-            samples.as_mut_slice().reverse();
-            self.buffer.extend_from_slice(&samples);
-            self.frames_remaing -= 1;
-        }
-        self.buffer.pop().map(Ok)
+    /// Multiply every decoded sample of the left and right channel by `left_gain` and `right_gain`.
+    ///
+    /// The result is clamped to the range of an [`i16`]. A gain of `1.0` does not change the samples.
+    #[must_use]
+    pub const fn with_gain(mut self, left_gain: f32, right_gain: f32) -> Self {
+        self.left_gain = Some(left_gain);
+        self.right_gain = Some(right_gain);
+        self
     }
 }
 
-impl<R: Read> Iterator for Decoder<R, Stereo> {
-    type Item = Result<i16, std::io::Error>;
+impl<R: Read + Seek> Decoder<R, Mono> {
+    /// Seek to `sample`, decoding `warmup_frames` frames before it to estimate the history.
+    ///
+    /// The history at `sample` is not known, so it is reset to zero at the first warmup
+    /// frame. ADPCM history converges quickly, so a few warmup frames (2 to 4 is usually
+    /// enough) make the history (almost) correct, which avoids a click after seeking.
+    /// The warmup frames must exist in the stream, if there are less than `warmup_frames`
+    /// frames before `sample`, the warmup starts at the start of the stream with the
+    /// history of the state the decoder was created with.
+    ///
+    /// `sample` is the position in the channel, like [`Decoder::position`]. Seeking past the
+    /// end moves to the end. Seeking is not supported after segments are added with
+    /// [`Decoder::chain`].
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let coefficients = [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frames = [0x03, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(4);
+    /// let mut decoder = Decoder::mono(Cursor::new(frames), Dsp::new(coefficients, 1000, -500), 4);
+    /// let all: Vec<i16> = decoder.by_ref().collect::<Result<_, _>>()?;
+    ///
+    /// // The warmup starts at the start of the stream, so the history is exact
+    /// decoder.seek_to_sample_warmup(30, 2)?;
+    /// assert_eq!(decoder.position(), 30);
+    /// assert_eq!(decoder.by_ref().collect::<Result<Vec<_>, _>>()?, all[30..]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// The stream doesn't need to start at the start of the reader, and seeking also works
+    /// after a read failed halfway through a frame.
+    ///
+    /// # Errors
+    /// Returns an error if seeking or reading the reader fails. Returns an error of kind
+    /// [`std::io::ErrorKind::Unsupported`] if segments were added with [`Decoder::chain`],
+    /// and of kind [`std::io::ErrorKind::InvalidInput`] if the reader is before the start
+    /// of the stream.
+    pub fn seek_to_sample_warmup(
+        &mut self,
+        sample: u64,
+        warmup_frames: u32,
+    ) -> Result<(), std::io::Error> {
+        if self.chained {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "seeking is not supported for chained segments",
+            ));
+        }
+        let sample = sample.min(self.samples_total);
+        let target = sample / u64::from(SAMPLES_PER_FRAME);
+        let target = u32::try_from(target).unwrap_or_else(|_| unreachable!());
+        let start = target.saturating_sub(warmup_frames);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.buffer.is_empty() && self.frames_remaing != 0 {
-            let mut left_frame = [0; 8];
-            let result = self.left_reader.read_exact(&mut left_frame);
-            if let Err(e) = result {
-                return Some(Err(e));
-            };
-            let mut right_frame = [0; 8];
-            let result = self
-                .right_reader
-                .as_mut()
-                .unwrap_or_else(|| unreachable!())
-                .read_exact(&mut right_frame);
-            if let Err(e) = result {
-                return Some(Err(e));
-            };
-            let left = self.left_state.decode_frame(left_frame);
-            let right = self
-                .right_state
-                .as_mut()
-                .unwrap_or_else(|| unreachable!())
-                .decode_frame(right_frame);
-            // Reverse samples and interleave
-            self.buffer.extend_from_slice(&[
-                left[13], right[13], left[12], right[12], left[11], right[11], left[10], right[10],
-                left[9], right[9], left[8], right[8], left[7], right[7], left[6], right[6],
-                left[5], right[5], left[4], right[4], left[3], right[3], left[2], right[2],
-                left[1], right[1], left[0], right[0],
-            ]);
-            self.frames_remaing -= 1;
-        }
-        self.buffer.pop().map(Ok)
-    }
-}
-
-impl<R: Read> Iterator for Decoder<R, StereoInterleaved> {
-    type Item = Result<i16, std::io::Error>;
+        let mut frame_size = u64::try_from(FRAME_SIZE).unwrap_or_else(|_| unreachable!());
+        if self.interleaved_channel.is_some() {
+            // The reader also contains the frames of the other channel
+            frame_size *= 2;
+        }
+        // Every read byte is counted, even by failed reads, so this is the start of the
+        // stream regardless of where a previous read stopped
+        let stream_start = self
+            .left_reader
+            .stream_position()?
+            .checked_sub(self.left_bytes_consumed)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "the reader is before the start of the stream",
+                )
+            })?;
+        let offset = u64::from(start) * frame_size;
+        self.left_reader
+            .seek(SeekFrom::Start(stream_start + offset))?;
+        self.left_bytes_consumed = offset;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.buffer.is_empty() && self.frames_remaing != 0 {
-            let mut left_frame = [0; 8];
-            let result = self.left_reader.read_exact(&mut left_frame);
-            if let Err(e) = result {
-                return Some(Err(e));
-            };
-            let mut right_frame = [0; 8];
-            let result = self.left_reader.read_exact(&mut right_frame);
-            if let Err(e) = result {
-                return Some(Err(e));
-            };
-            let left = self.left_state.decode_frame(left_frame);
-            let right = self
-                .right_state
-                .as_mut()
-                .unwrap_or_else(|| unreachable!())
-                .decode_frame(right_frame);
-            // Reverse samples and interleave
-            self.buffer.extend_from_slice(&[
-                left[13], right[13], left[12], right[12], left[11], right[11], left[10], right[10],
-                left[9], right[9], left[8], right[8], left[7], right[7], left[6], right[6],
-                left[5], right[5], left[4], right[4], left[3], right[3], left[2], right[2],
-                left[1], right[1], left[0], right[0],
-            ]);
-            self.frames_remaing -= 2;
-        }
-        self.buffer.pop().map(Ok)
+        self.frames_remaing = self.frames_total - start;
+        self.buffer.clear();
+        self.failed = false;
+        if start == 0 {
+            let (hist1, hist2) = self.initial_history;
+            self.left_state.set_history(hist1, hist2);
+        } else {
+            self.left_state.set_history(0, 0);
+        }
+        self.skip_frames(target - start)?;
+        self.skip_samples(sample % u64::from(SAMPLES_PER_FRAME))
+    }
+
+    /// Decode a mono audio stream.
+    ///
+    /// The amount of frames is calculated from the remaining length of the reader.
+    ///
+    /// # Errors
+    /// Returns an error if seeking fails or if the reader contains too many frames.
+    pub fn mono_from_len(mut reader: R, state: Dsp) -> Result<Self, std::io::Error> {
+        let frames = remaining_frames(&mut reader, 1)?;
+        Ok(Self::mono(reader, state, frames))
+    }
+
+    /// Decode the samples `start..end` of a mono audio stream, seeking to the frame that
+    /// contains `start`.
+    ///
+    /// The frames before `start` are not decoded, so this is much faster than
+    /// [`Decoder::mono_range`] for a range far into the stream. However, `state` is used
+    /// at the start frame, so the history is only correct if `state` contains the history
+    /// of that frame, for example from a seek table. Otherwise the first samples can be
+    /// wrong, which is usually a short click. The position of the reader must be the start
+    /// of the stream, and [`Decoder::position`] starts counting at the start frame.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(3);
+    /// let all: Vec<i16> = Decoder::mono(Cursor::new(&frames), Dsp::default(), 3).collect::<Result<_, _>>()?;
+    /// // Without coefficients the history is not used, so the samples are exact
+    /// let range = Decoder::mono_range_seek(Cursor::new(&frames), Dsp::default(), 30, 42)?;
+    /// assert_eq!(range.collect::<Result<Vec<_>, _>>()?, all[30..]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if `start` is larger than `end`, if `end` does not fit in a [`u32`],
+    /// or if seeking or reading the samples before `start` in the start frame fails.
+    pub fn mono_range_seek(
+        mut reader: R,
+        state: Dsp,
+        start: u64,
+        end: u64,
+    ) -> Result<Self, std::io::Error> {
+        let end = range_end(start, end)?;
+        let frames = start / u64::from(SAMPLES_PER_FRAME);
+        let offset = frames * u64::try_from(FRAME_SIZE).unwrap_or_else(|_| unreachable!());
+        reader.seek(SeekFrom::Current(
+            i64::try_from(offset).unwrap_or_else(|_| unreachable!()),
+        ))?;
+        let skipped = u32::try_from(frames).unwrap_or_else(|_| unreachable!()) * SAMPLES_PER_FRAME;
+        let mut decoder = Self::mono_samples(reader, state, end - skipped);
+        decoder.skip_samples(start % u64::from(SAMPLES_PER_FRAME))?;
+        Ok(decoder)
+    }
+}
+
+impl<R: Read + Seek> Decoder<R, Stereo> {
+    /// Decode a stereo audio stream where each channel has their own buffer.
+    ///
+    /// The amount of frames is calculated from the remaining length of the readers.
+    /// If the readers have a different length, the shortest length is used.
+    ///
+    /// # Errors
+    /// Returns an error if seeking fails or if the readers contain too many frames.
+    pub fn stereo_from_len(
+        mut left_reader: R,
+        left_state: Dsp,
+        mut right_reader: R,
+        right_state: Dsp,
+    ) -> Result<Self, std::io::Error> {
+        let left_frames = remaining_frames(&mut left_reader, 1)?;
+        let right_frames = remaining_frames(&mut right_reader, 1)?;
+        Ok(Self::stereo(
+            left_reader,
+            left_state,
+            right_reader,
+            right_state,
+            left_frames.min(right_frames),
+        ))
+    }
+}
+
+impl<R: Read + Seek> Decoder<R, StereoInterleaved> {
+    /// Decode a stereo audio stream interleaved per frame.
+    ///
+    /// The amount of frames is calculated from the remaining length of the reader.
+    ///
+    /// # Errors
+    /// Returns an error if seeking fails or if the reader contains too many frames.
+    pub fn interleaved_stereo_from_len(
+        mut reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+    ) -> Result<Self, std::io::Error> {
+        let channel_frames = remaining_frames(&mut reader, 2)?;
+        Ok(Self::interleaved_stereo(
+            reader,
+            left_state,
+            right_state,
+            channel_frames,
+        ))
+    }
+}
+
+impl Decoder<Cursor<Vec<u8>>, Mono> {
+    /// Decode a mono audio stream that is already in memory.
+    ///
+    /// The amount of frames is calculated from the length of `data`, an incomplete frame at
+    /// the end is ignored. Reading from memory can't fail, so the samples are returned
+    /// without an [`std::io::Error`].
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    ///
+    /// let data = vec![0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let samples: Vec<i16> = Decoder::mono_bytes(data, Dsp::default()).collect();
+    /// assert_eq!(samples.len(), 14);
+    /// ```
+    pub fn mono_bytes(data: Vec<u8>, state: Dsp) -> impl Iterator<Item = i16> {
+        let frames = frames_from_bytes(data.len());
+        infallible(Self::mono(Cursor::new(data), state, frames))
+    }
+}
+
+impl Decoder<Cursor<Vec<u8>>, Stereo> {
+    /// Decode a stereo audio stream where each channel is already in memory.
+    ///
+    /// The amount of frames is calculated from the length of the shortest buffer. See
+    /// [`Decoder::mono_bytes`].
+    pub fn stereo_bytes(
+        left_data: Vec<u8>,
+        left_state: Dsp,
+        right_data: Vec<u8>,
+        right_state: Dsp,
+    ) -> impl Iterator<Item = i16> {
+        let frames = frames_from_bytes(left_data.len().min(right_data.len()));
+        infallible(Self::stereo(
+            Cursor::new(left_data),
+            left_state,
+            Cursor::new(right_data),
+            right_state,
+            frames,
+        ))
+    }
+}
+
+impl Decoder<Cursor<Vec<u8>>, StereoInterleaved> {
+    /// Decode an interleaved stereo audio stream that is already in memory.
+    ///
+    /// The amount of frames is calculated from the length of `data`, an incomplete pair of
+    /// frames at the end is ignored. See [`Decoder::mono_bytes`].
+    pub fn interleaved_stereo_bytes(
+        data: Vec<u8>,
+        left_state: Dsp,
+        right_state: Dsp,
+    ) -> impl Iterator<Item = i16> {
+        let channel_frames = frames_from_bytes(data.len()) / 2;
+        infallible(Self::interleaved_stereo(
+            Cursor::new(data),
+            left_state,
+            right_state,
+            channel_frames,
+        ))
+    }
+}
+
+impl<R: Read, C: Channels> Decoder<R, C> {
+    /// The amount of samples in the output for one frame of every channel.
+    ///
+    /// This is useful for sizing buffers in code that is generic over the [`Channels`].
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Mono, Stereo, StereoInterleaved};
+    /// use std::io::Cursor;
+    ///
+    /// type Reader = Cursor<Vec<u8>>;
+    /// assert_eq!(Decoder::<Reader, Mono>::OUTPUT_SAMPLES_PER_STEP, 14);
+    /// assert_eq!(Decoder::<Reader, Stereo>::OUTPUT_SAMPLES_PER_STEP, 28);
+    /// assert_eq!(Decoder::<Reader, StereoInterleaved>::OUTPUT_SAMPLES_PER_STEP, 28);
+    /// assert_eq!(Decoder::<Reader, Mono>::INPUT_BYTES_PER_STEP, 8);
+    /// assert_eq!(Decoder::<Reader, Stereo>::INPUT_BYTES_PER_STEP, 16);
+    /// assert_eq!(Decoder::<Reader, StereoInterleaved>::INPUT_BYTES_PER_STEP, 16);
+    /// ```
+    pub const OUTPUT_SAMPLES_PER_STEP: usize = 14 * C::CHANNEL_COUNT;
+
+    /// The amount of bytes read for one frame of every channel.
+    ///
+    /// For [`Stereo`] this is the sum of both readers. See [`Decoder::OUTPUT_SAMPLES_PER_STEP`].
+    pub const INPUT_BYTES_PER_STEP: usize = FRAME_SIZE * C::CHANNEL_COUNT;
+
+    /// Get the next sample without consuming it.
+    ///
+    /// If needed, the next frame(s) is decoded. Calling `peek` multiple times returns the
+    /// same sample, which is also the sample returned by the next call to `next`.
+    /// If decoding fails, the error is returned by `peek` instead of `next`.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let mut decoder = Decoder::mono(Cursor::new(frame), Dsp::default(), 1);
+    /// let first = decoder.peek().unwrap().unwrap();
+    /// assert_eq!(decoder.peek().unwrap().unwrap(), first);
+    /// assert_eq!(decoder.next().unwrap().unwrap(), first);
+    /// ```
+    pub fn peek(&mut self) -> Option<Result<i16, std::io::Error>> {
+        if self.failed || Self::position(self) >= self.samples_total {
+            return None;
+        }
+        if let Err(e) = self.fill_buffer_once() {
+            return Some(Err(e));
+        }
+        self.buffer.front().map(Ok)
+    }
+
+    /// Decode the next frame(s) into the buffer if it is empty, remembering if it fails.
+    fn fill_buffer_once(&mut self) -> Result<(), std::io::Error> {
+        let result = C::fill_buffer(self);
+        self.failed = result.is_err();
+        result
+    }
+
+    /// Check if the next call to [`Iterator::next`] reads the next frame(s).
+    #[cfg(feature = "tokio")]
+    fn needs_frames(&self) -> bool {
+        !self.failed
+            && self.buffer.is_empty()
+            && self.frames_remaing >= C::FRAMES_PER_STEP
+            && Self::position(self) < self.samples_total
+    }
+
+    /// Decode the next frame(s) with `decode`, for [`Decoder::frames`](Decoder<R, Mono>::frames).
+    ///
+    /// Like [`Iterator::next`], this returns `None` after an error or at the end of the
+    /// output. The frame(s) are returned with the amount of samples per channel that are in
+    /// the output, which is less than 14 if the rest of the frame is padding.
+    fn next_frames<T>(
+        &mut self,
+        decode: impl FnOnce(&mut Self) -> Result<T, std::io::Error>,
+    ) -> Option<Result<(T, usize), std::io::Error>> {
+        let position = Self::position(self);
+        if self.failed || position >= self.samples_total || self.frames_remaing < C::FRAMES_PER_STEP
+        {
+            return None;
+        }
+        match decode(self) {
+            Ok(frames) => {
+                let channels = u64::try_from(C::CHANNEL_COUNT).unwrap_or_else(|_| unreachable!());
+                let remaining = (self.samples_total - position) / channels;
+                let len = usize::try_from(remaining.min(u64::from(SAMPLES_PER_FRAME)))
+                    .unwrap_or_else(|_| unreachable!());
+                Some(Ok((frames, len)))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Push samples to `producer` until it returns `false` or the audio stream ends.
+    ///
+    /// This is meant for pushing to a ringbuffer for real-time playback, without an
+    /// intermediate buffer. `producer` should return `false` if the sample didn't fit, the
+    /// sample is then kept for the next call. Returns the amount of samples that were pushed.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::collections::VecDeque;
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let mut decoder = Decoder::mono(Cursor::new(frames), Dsp::default(), 2);
+    /// let mut ringbuffer = VecDeque::with_capacity(20);
+    /// let mut push = |sample| ringbuffer.len() < 20 && { ringbuffer.push_back(sample); true };
+    /// assert_eq!(decoder.fill_ringbuffer(&mut push)?, 20);
+    /// ringbuffer.drain(..10);
+    /// let mut push = |sample| ringbuffer.len() < 20 && { ringbuffer.push_back(sample); true };
+    /// assert_eq!(decoder.fill_ringbuffer(&mut push)?, 8);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The samples before the error are already
+    /// pushed to `producer`.
+    pub fn fill_ringbuffer(
+        &mut self,
+        producer: &mut impl FnMut(i16) -> bool,
+    ) -> Result<usize, std::io::Error> {
+        let mut pushed = 0;
+        while let Some(sample) = self.peek() {
+            if !producer(sample?) {
+                break;
+            }
+            self.buffer.pop_front();
+            pushed += 1;
+        }
+        Ok(pushed)
+    }
+
+    /// The amount of samples that are already returned by the decoder.
+    ///
+    /// For stereo audio streams this counts the interleaved samples, so it is twice the
+    /// position in one channel. Skipped samples are counted as returned.
+    ///
+    /// On a `&mut Decoder` this method is shadowed by [`Iterator::position`], use
+    /// `Decoder::position(&decoder)` instead.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let mut decoder = Decoder::stereo(Cursor::new([0; 16]), Dsp::default(), Cursor::new([0; 16]), Dsp::default(), 2);
+    /// assert_eq!(decoder.position(), 0);
+    /// decoder.by_ref().take(3).for_each(drop);
+    /// assert_eq!(decoder.position(), 3);
+    /// decoder.by_ref().for_each(drop);
+    /// assert_eq!(decoder.position(), 56);
+    /// ```
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        // Every step decodes one frame per channel, but counts `FRAMES_PER_STEP` frames
+        let channels = u64::try_from(C::CHANNEL_COUNT).unwrap_or_else(|_| unreachable!());
+        let steps = (self.frames_total - self.frames_remaing) / C::FRAMES_PER_STEP;
+        let decoded = u64::from(steps) * channels * u64::from(SAMPLES_PER_FRAME);
+        decoded - u64::try_from(self.buffer.len()).unwrap_or_else(|_| unreachable!())
+    }
+
+    /// The index of the last frame that was decoded, or that failed to decode.
+    ///
+    /// The index is the position of the frame in one channel, starting at zero. This is
+    /// useful for finding where a stream is truncated or corrupt after an error. Returns
+    /// `None` if no frame is decoded yet.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// // The third frame is truncated
+    /// let mut decoder = Decoder::mono(Cursor::new([0; 20]), Dsp::default(), 3);
+    /// assert_eq!(decoder.last_frame_index(), None);
+    /// assert!(decoder.by_ref().any(|sample| sample.is_err()));
+    /// assert_eq!(decoder.last_frame_index(), Some(2));
+    /// ```
+    #[must_use]
+    pub const fn last_frame_index(&self) -> Option<u32> {
+        self.last_frame_index
+    }
+
+    /// The amount of samples that are already decoded and can be returned without reading.
+    ///
+    /// The padding of the last frame is not counted.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let mut decoder = Decoder::mono_samples(Cursor::new([0; 16]), Dsp::default(), 20);
+    /// assert_eq!(decoder.buffered(), 0);
+    /// decoder.next();
+    /// assert_eq!(decoder.buffered(), 13);
+    /// decoder.by_ref().take(13).for_each(drop);
+    /// decoder.next();
+    /// assert_eq!(decoder.buffered(), 5);
+    /// ```
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        let remaining = self.samples_total.saturating_sub(Self::position(self));
+        usize::try_from(remaining).map_or(self.buffer.len(), |remaining| {
+            remaining.min(self.buffer.len())
+        })
+    }
+
+    /// The total duration in seconds of the audio stream at `sample_rate` samples per second.
+    ///
+    /// This uses the amount of samples in the output, so it does not change while decoding.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let decoder = Decoder::interleaved_stereo_samples(Cursor::new([]), Dsp::default(), Dsp::default(), 48_000);
+    /// assert_eq!(decoder.duration(32_000), 1.5);
+    /// ```
+    #[must_use]
+    pub fn duration(&self, sample_rate: u32) -> f64 {
+        let channels = u64::try_from(C::CHANNEL_COUNT).unwrap_or_else(|_| unreachable!());
+        let samples = self.samples_total / channels;
+        #[allow(
+            clippy::as_conversions,
+            clippy::cast_precision_loss,
+            reason = "The amount of samples is less than 2^53, so it fits exactly in f64."
+        )]
+        let samples = samples as f64;
+        samples / f64::from(sample_rate)
+    }
+
+    /// Collect statistics about the frames that are decoded from now on.
+    ///
+    /// The statistics are available via [`Decoder::stats`]. This is useful for finding out
+    /// why decoded audio sounds wrong.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let coefficients = [4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frames = [0x0C, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x31, 0, 0, 0, 0, 0, 0, 0];
+    /// let mut decoder =
+    ///     Decoder::mono(Cursor::new(frames), Dsp::from_coefficients(coefficients), 2).with_stats();
+    /// decoder.by_ref().for_each(drop);
+    /// let stats = decoder.stats();
+    /// assert_eq!(stats.frames(), 2);
+    /// assert_eq!((stats.scales[0xC], stats.scales[0x1]), (1, 1));
+    /// assert_eq!((stats.coefficient_indices[0], stats.coefficient_indices[3]), (1, 1));
+    /// assert_eq!(stats.max_sample, Some(i16::MAX));
+    /// assert!(stats.clamped_samples > 0);
+    /// ```
+    #[must_use]
+    pub const fn with_stats(mut self) -> Self {
+        self.stats = Some(DecodeStats::new());
+        self
+    }
+
+    /// The statistics about the decoded frames.
+    ///
+    /// If [`Decoder::with_stats`] is not used, nothing is counted.
+    #[must_use]
+    pub fn stats(&self) -> &DecodeStats {
+        /// Statistics returned when they are not collected
+        const EMPTY: DecodeStats = DecodeStats::new();
+        self.stats.as_ref().unwrap_or(&EMPTY)
+    }
+
+    /// Call `f` for every frame that is decoded from now on.
+    ///
+    /// `f` is called with the index of the frame in one channel and the decoded samples,
+    /// after the gain is applied. For stereo audio streams, the samples of both channels are
+    /// interleaved like the output. This does not change the output, so it's useful for
+    /// visualizing the audio or logging while decoding. Frames that are skipped are also
+    /// passed to `f`.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let peaks = Arc::new(Mutex::new(Vec::new()));
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let decoder = Decoder::mono(Cursor::new(frames), Dsp::default(), 2).on_frame({
+    ///     let peaks = Arc::clone(&peaks);
+    ///     move |index, samples| {
+    ///         let peak = samples.iter().map(|sample| sample.unsigned_abs()).max();
+    ///         peaks.lock().unwrap().push((index, peak.unwrap()));
+    ///     }
+    /// });
+    /// assert_eq!(decoder.count(), 28);
+    /// assert_eq!(*peaks.lock().unwrap(), [(0, 8), (1, 8)]);
+    /// ```
+    #[must_use]
+    pub fn on_frame(mut self, f: impl FnMut(u32, &[i16]) + Send + 'static) -> Self {
+        self.on_frame = Some(Box::new(f));
+        self
+    }
+
+    /// Pass the frame(s) that were just decoded to the callback of [`Decoder::on_frame`].
+    fn notify_frame(&mut self, left: &[i16; 14], right: Option<&[i16; 14]>) {
+        let (Some(on_frame), Some(index)) = (self.on_frame.as_mut(), self.last_frame_index) else {
+            return;
+        };
+        match right {
+            None => on_frame(index, left),
+            Some(right) => {
+                let mut interleaved = [0; 28];
+                for (i, (left, right)) in left.iter().zip(right).enumerate() {
+                    interleaved[i * 2] = *left;
+                    interleaved[i * 2 + 1] = *right;
+                }
+                on_frame(index, &interleaved);
+            }
+        }
+    }
+
+    /// The amount of bytes that are read from the reader(s).
+    ///
+    /// This is the offset in the compressed data, which is useful for continuing to parse a
+    /// container after the audio stream. For [`Stereo`] the bytes of both readers are added,
+    /// use [`Decoder::bytes_consumed_per_reader`] to get them separately. Bytes read by a
+    /// failed read are also counted, so this matches the position in the reader(s).
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let mut decoder = Decoder::interleaved_stereo(Cursor::new([0; 32]), Dsp::default(), Dsp::default(), 2);
+    /// assert_eq!(decoder.bytes_consumed(), 0);
+    /// decoder.next();
+    /// assert_eq!(decoder.bytes_consumed(), 16);
+    /// decoder.by_ref().for_each(drop);
+    /// assert_eq!(decoder.bytes_consumed(), 32);
+    /// ```
+    #[must_use]
+    pub const fn bytes_consumed(&self) -> u64 {
+        self.left_bytes_consumed + self.right_bytes_consumed
+    }
+}
+
+impl<R: Read + Seek, C: Channels> Decoder<R, C> {
+    /// Check that the remaining length of the reader(s) matches the amount of remaining frames.
+    ///
+    /// This is not done automatically as a stream can be embedded in a larger file.
+    /// For [`Stereo`] both readers are checked, the left reader first.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp, LengthError};
+    /// use std::io::Cursor;
+    ///
+    /// let mut decoder = Decoder::mono(Cursor::new([0; 20]), Dsp::default(), 3);
+    /// let Err(LengthError::Mismatch(mismatch)) = decoder.validate_length() else {
+    ///     panic!("Length should not match");
+    /// };
+    /// assert_eq!((mismatch.expected, mismatch.actual), (24, 20));
+    /// assert_eq!(mismatch.difference(), -4);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`LengthError::Mismatch`] if the length does not match, or [`LengthError::Io`]
+    /// if getting the length of the reader(s) fails.
+    pub fn validate_length(&mut self) -> Result<(), LengthError> {
+        // The frames of the chained segments are in other readers
+        let mut frames = u64::from(self.frames_remaing - self.segment_frames);
+        if let Some(group) = &self.group {
+            // The frames of the current group are already read
+            frames -= u64::try_from(group.len - group.next).unwrap_or_else(|_| unreachable!()) * 2;
+        }
+        let mut expected = frames * u64::try_from(FRAME_SIZE).unwrap_or_else(|_| unreachable!());
+        if self.interleaved_channel.is_some() {
+            // The reader also contains the frames of the other channel
+            expected *= 2;
+        }
+        let readers = std::iter::once(&mut self.left_reader).chain(self.right_reader.as_mut());
+        for reader in readers {
+            let actual = remaining_bytes(reader)?;
+            if actual != expected {
+                return Err(LengthError::Mismatch(LengthMismatch { expected, actual }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a snapshot of the current state of the decoder.
+    ///
+    /// The snapshot can be restored with [`Decoder::restore`].
+    ///
+    /// # Errors
+    /// Returns an error if getting the position of the reader(s) fails.
+    pub fn snapshot(&mut self) -> Result<DecoderState<C>, std::io::Error> {
+        let left_position = self.left_reader.stream_position()?;
+        let right_position = self
+            .right_reader
+            .as_mut()
+            .map(Seek::stream_position)
+            .transpose()?;
+        Ok(DecoderState {
+            left_position,
+            right_position,
+            left_state: self.left_state,
+            right_state: self.right_state,
+            frames_remaing: self.frames_remaing,
+            buffer: self.buffer.clone(),
+            group: self.group.clone(),
+            left_bytes_consumed: self.left_bytes_consumed,
+            right_bytes_consumed: self.right_bytes_consumed,
+            _phantom_data: PhantomData,
+        })
+    }
+
+    /// Restore the decoder to a snapshot.
+    ///
+    /// The reader(s) are seeked to where they were when the snapshot was taken.
+    /// Decoding continues with exactly the same state, so the output is identical.
+    ///
+    /// # Errors
+    /// Returns an error if seeking the reader(s) fails. The DSP state is not changed,
+    /// but the reader(s) may have moved.
+    pub fn restore(&mut self, state: DecoderState<C>) -> Result<(), std::io::Error> {
+        self.left_reader
+            .seek(SeekFrom::Start(state.left_position))?;
+        if let (Some(reader), Some(position)) = (self.right_reader.as_mut(), state.right_position) {
+            reader.seek(SeekFrom::Start(position))?;
+        }
+        self.left_state = state.left_state;
+        self.right_state = state.right_state;
+        self.frames_remaing = state.frames_remaing;
+        self.buffer = state.buffer;
+        self.group = state.group;
+        self.left_bytes_consumed = state.left_bytes_consumed;
+        self.right_bytes_consumed = state.right_bytes_consumed;
+        self.failed = false;
+        Ok(())
+    }
+}
+
+impl<R: Read, C: Channels> Decoder<R, C> {
+    /// Output the samples as little-endian bytes.
+    ///
+    /// For stereo the samples are still interleaved per sample.
+    pub fn le_bytes(self) -> impl Iterator<Item = Result<[u8; 2], std::io::Error>> {
+        self.map(|sample| sample.map(i16::to_le_bytes))
+    }
+
+    /// Output the samples as big-endian bytes.
+    ///
+    /// For stereo the samples are still interleaved per sample.
+    pub fn be_bytes(self) -> impl Iterator<Item = Result<[u8; 2], std::io::Error>> {
+        self.map(|sample| sample.map(i16::to_be_bytes))
+    }
+
+    /// Convert the decoder into a reader that outputs the samples as little-endian bytes.
+    ///
+    /// For stereo the samples are still interleaved per sample.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::{Cursor, Read};
+    ///
+    /// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let decoder = Decoder::mono(Cursor::new(frame), Dsp::default(), 1);
+    /// let mut bytes = Vec::new();
+    /// decoder.into_pcm_reader().read_to_end(&mut bytes)?;
+    /// assert_eq!(bytes.len(), 28);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn into_pcm_reader(self) -> impl Read {
+        PcmReader::new(self)
+    }
+
+    /// Output the samples in chunks of `chunk_size` samples.
+    ///
+    /// For stereo the samples are still interleaved per sample, and both channels count
+    /// towards the chunk size. The last chunk is shorter if the amount of samples is not a
+    /// multiple of `chunk_size`. After an error no more chunks are returned, and the samples
+    /// of the chunk that was being collected are discarded.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(3);
+    /// let decoder = Decoder::mono(Cursor::new(frames), Dsp::default(), 3);
+    /// let chunks = decoder.chunks(16).collect::<Result<Vec<_>, _>>()?;
+    /// let lengths: Vec<usize> = chunks.iter().map(Vec::len).collect();
+    /// assert_eq!(lengths, [16, 16, 10]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks(
+        mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<Vec<i16>, std::io::Error>> {
+        assert!(chunk_size != 0, "The chunk size cannot be zero");
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let mut chunk = Vec::with_capacity(chunk_size);
+            for sample in self.by_ref().take(chunk_size) {
+                match sample {
+                    Ok(sample) => chunk.push(sample),
+                    Err(e) => {
+                        failed = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            (!chunk.is_empty()).then_some(Ok(chunk))
+        })
+    }
+}
+
+/// Reader that outputs the samples of a decoder as little-endian bytes.
+struct PcmReader<I: Iterator<Item = Result<i16, std::io::Error>>> {
+    /// The decoder for the samples
+    decoder: I,
+    /// The second byte of a sample that did not fit in the previous buffer
+    pending: Option<u8>,
+    /// An error that happened after some bytes were already read
+    error: Option<std::io::Error>,
+}
+
+impl<I: Iterator<Item = Result<i16, std::io::Error>>> PcmReader<I> {
+    /// Create a reader for the samples of `decoder`.
+    const fn new(decoder: I) -> Self {
+        Self {
+            decoder,
+            pending: None,
+            error: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<i16, std::io::Error>>> Read for PcmReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let mut written = 0;
+        while written < buf.len() {
+            if let Some(byte) = self.pending.take() {
+                buf[written] = byte;
+                written += 1;
+                continue;
+            }
+            match self.decoder.next() {
+                Some(Ok(sample)) => {
+                    let [low, high] = sample.to_le_bytes();
+                    buf[written] = low;
+                    written += 1;
+                    self.pending = Some(high);
+                }
+                Some(Err(e)) if written == 0 => return Err(e),
+                Some(Err(e)) => {
+                    // Return the error on the next call
+                    self.error = Some(e);
+                    break;
+                }
+                None => break,
+            }
+        }
+        Ok(written)
+    }
+}
+
+impl<R: Read> Decoder<R, Mono> {
+    /// Read and decode the next frame.
+    fn decode_next_frame(&mut self) -> Result<[i16; 14], std::io::Error> {
+        self.next_segment();
+        self.last_frame_index = Some(self.frames_total - self.frames_remaing);
+        let mut frame = [0; FRAME_SIZE];
+        match self.interleaved_channel {
+            None => read_counted(
+                &mut self.left_reader,
+                &mut frame,
+                &mut self.left_bytes_consumed,
+            )?,
+            Some(Channel::Left) => {
+                read_counted(
+                    &mut self.left_reader,
+                    &mut frame,
+                    &mut self.left_bytes_consumed,
+                )?;
+                read_counted(
+                    &mut self.left_reader,
+                    &mut [0; FRAME_SIZE],
+                    &mut self.left_bytes_consumed,
+                )?;
+            }
+            Some(Channel::Right) => {
+                read_counted(
+                    &mut self.left_reader,
+                    &mut [0; FRAME_SIZE],
+                    &mut self.left_bytes_consumed,
+                )?;
+                read_counted(
+                    &mut self.left_reader,
+                    &mut frame,
+                    &mut self.left_bytes_consumed,
+                )?;
+            }
+        }
+        self.frames_remaing -= 1;
+        let mut samples = self.left_state.decode_frame(frame);
+        if let Some(stats) = self.stats.as_mut() {
+            stats.record(frame[0], &samples, self.left_state.clamp_range());
+        }
+        apply_gain(&mut samples, self.left_gain);
+        self.notify_frame(&samples, None);
+        Ok(samples)
+    }
+
+    /// Decode the next frame into the buffer if it is empty.
+    fn fill_buffer(&mut self) -> Result<(), std::io::Error> {
+        if self.buffer.is_empty() && self.frames_remaing != 0 {
+            let samples = self.decode_next_frame()?;
+            self.buffer.fill(&samples);
+        }
+        Ok(())
+    }
+
+    /// Skip the next `frames` frames.
+    ///
+    /// The skipped frames are still decoded so that the DSP state is correct for
+    /// the frames after it. Any samples that were already decoded are discarded.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The frames before the failing frame
+    /// are skipped.
+    pub fn skip_frames(&mut self, frames: u32) -> Result<(), std::io::Error> {
+        self.buffer.clear();
+        for _ in 0..frames.min(self.frames_remaing) {
+            self.decode_next_frame()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the history of the DSP state with `hist1` and `hist2`.
+    ///
+    /// The new history is used starting from the next frame that is decoded, samples
+    /// that are already decoded are not affected.
+    pub fn set_history(&mut self, hist1: i16, hist2: i16) {
+        self.left_state.set_history(hist1, hist2);
+    }
+
+    /// Output the decoded frames instead of individual samples.
+    ///
+    /// Every item is a frame with the amount of its samples that are in the output. This
+    /// is 14, except for the last frame if the rest of it is padding, so `frame[..len]`
+    /// contains exactly the same samples as the iterator. Nothing is allocated per frame.
+    ///
+    /// Any samples that were already decoded but not yet returned are discarded. Like the
+    /// iterator, an error is returned once, after which no more frames are returned.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+    /// let samples: Vec<i16> = Decoder::mono_samples(Cursor::new(&frames), Dsp::default(), 20)
+    ///     .collect::<Result<_, _>>()?;
+    /// let mut decoded = Vec::new();
+    /// for frame in Decoder::mono_samples(Cursor::new(&frames), Dsp::default(), 20).frames() {
+    ///     let (frame, len) = frame?;
+    ///     decoded.extend_from_slice(&frame[..len]);
+    /// }
+    /// assert_eq!(decoded, samples);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn frames(mut self) -> impl Iterator<Item = Result<([i16; 14], usize), std::io::Error>> {
+        self.buffer.clear();
+        std::iter::from_fn(move || self.next_frames(Self::decode_next_frame))
+    }
+}
+
+impl<R: Read> Decoder<R, Stereo> {
+    /// Read and decode the next frame of both channels.
+    fn decode_next_frames(&mut self) -> Result<([i16; 14], [i16; 14]), std::io::Error> {
+        self.last_frame_index = Some(self.frames_total - self.frames_remaing);
+        let mut left_frame = [0; FRAME_SIZE];
+        read_counted(
+            &mut self.left_reader,
+            &mut left_frame,
+            &mut self.left_bytes_consumed,
+        )?;
+        let mut right_frame = [0; FRAME_SIZE];
+        read_counted(
+            self.right_reader.as_mut().unwrap_or_else(|| unreachable!()),
+            &mut right_frame,
+            &mut self.right_bytes_consumed,
+        )?;
+        let mut left = self.left_state.decode_frame(left_frame);
+        let mut right = self
+            .right_state
+            .as_mut()
+            .unwrap_or_else(|| unreachable!())
+            .decode_frame(right_frame);
+        if let Some(stats) = self.stats.as_mut() {
+            stats.record(left_frame[0], &left, self.left_state.clamp_range());
+            let right_state = self.right_state.unwrap_or_else(|| unreachable!());
+            stats.record(right_frame[0], &right, right_state.clamp_range());
+        }
+        apply_gain(&mut left, self.left_gain);
+        apply_gain(&mut right, self.right_gain);
+        self.notify_frame(&left, Some(&right));
+        self.frames_remaing -= 1;
+        Ok((left, right))
+    }
+
+    /// Decode the next frame of both channels into the buffer if it is empty.
+    fn fill_buffer(&mut self) -> Result<(), std::io::Error> {
+        if self.buffer.is_empty() && self.frames_remaing != 0 {
+            let (left, right) = self.decode_next_frames()?;
+            self.buffer.fill_interleaved(&left, &right);
+        }
+        Ok(())
+    }
+
+    /// Skip the next `channel_frames` frames of both channels.
+    ///
+    /// The skipped frames are still decoded so that the DSP state is correct for
+    /// the frames after it. Any samples that were already decoded are discarded.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The frames before the failing frame
+    /// are skipped.
+    pub fn skip_frames(&mut self, channel_frames: u32) -> Result<(), std::io::Error> {
+        self.buffer.clear();
+        for _ in 0..channel_frames.min(self.frames_remaing) {
+            self.decode_next_frames()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the history of the DSP state of both channels.
+    ///
+    /// The new history is used starting from the next frame that is decoded, samples
+    /// that are already decoded are not affected.
+    pub fn set_history(
+        &mut self,
+        left_hist1: i16,
+        left_hist2: i16,
+        right_hist1: i16,
+        right_hist2: i16,
+    ) {
+        self.left_state.set_history(left_hist1, left_hist2);
+        self.right_state
+            .as_mut()
+            .unwrap_or_else(|| unreachable!())
+            .set_history(right_hist1, right_hist2);
+    }
+
+    /// Output the decoded frames of both channels instead of individual samples.
+    ///
+    /// Every item is the next frame of the left and right channel, with the amount of
+    /// samples per channel that are in the output. See
+    /// [`Decoder::frames`](Decoder<R, Mono>::frames) for details.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77]);
+    /// let right = Cursor::new([0; 8]);
+    /// let decoder = Decoder::stereo_samples(left, Dsp::default(), right, Dsp::default(), 10);
+    /// let frames = decoder.frames().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(frames, [([7; 14], [0; 14], 10)]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn frames(
+        mut self,
+    ) -> impl Iterator<Item = Result<([i16; 14], [i16; 14], usize), std::io::Error>> {
+        self.buffer.clear();
+        std::iter::from_fn(move || {
+            let frames = self.next_frames(Self::decode_next_frames)?;
+            Some(frames.map(|((left, right), len)| (left, right, len)))
+        })
+    }
+
+    /// Output the samples as `(left, right)` pairs.
+    ///
+    /// If an odd number of samples was already consumed, the pairs will be misaligned.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77]);
+    /// let right = Cursor::new([0; 8]);
+    /// let decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 1);
+    /// for pair in decoder.pairs() {
+    ///     assert_eq!(pair?, (7, 0));
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn pairs(self) -> impl Iterator<Item = Result<(i16, i16), std::io::Error>> {
+        pair_samples(self)
+    }
+
+    /// Output the average of the left and right channel, to get a mono audio stream.
+    ///
+    /// The average is rounded half away from zero. If an odd number of samples was already consumed, the samples of two different
+    /// positions are averaged.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77]);
+    /// let right = Cursor::new([0x00, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22]);
+    /// let decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 1);
+    /// for sample in decoder.downmix_mono() {
+    ///     // The average of 7 and 2 is 4.5
+    ///     assert_eq!(sample?, 5);
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn downmix_mono(self) -> impl Iterator<Item = Result<i16, std::io::Error>> {
+        downmix(self)
+    }
+
+    /// Split the output into an iterator for the left and the right channel.
+    ///
+    /// The iterators can be consumed independently, see [`SplitChannel`]. If an odd number
+    /// of samples was already consumed, the channels will be swapped.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77]);
+    /// let right = Cursor::new([0x00, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22]);
+    /// let decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 1);
+    /// let (left, right) = decoder.split();
+    /// // The left channel is consumed completely before the right channel
+    /// assert_eq!(left.collect::<Result<Vec<_>, _>>()?, [7; 14]);
+    /// assert_eq!(right.collect::<Result<Vec<_>, _>>()?, [2; 14]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn split(self) -> (SplitChannel<R, Stereo>, SplitChannel<R, Stereo>) {
+        SplitChannel::new(self)
+    }
+
+    /// Decode samples into separate buffers for the left and right channel.
+    ///
+    /// Returns the amount of samples written per channel, this is at most the length
+    /// of the shortest buffer. Any remaining decoded samples are kept for the next call.
+    ///
+    /// If an odd number of samples was already consumed, the channels will be swapped.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let left = Cursor::new([0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77]);
+    /// let right = Cursor::new([0; 8]);
+    /// let mut decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 1);
+    /// let (mut left, mut right) = ([0; 20], [0; 20]);
+    /// assert_eq!(decoder.read_planar(&mut left, &mut right)?, 14);
+    /// assert_eq!(left[..14], [7; 14]);
+    /// assert_eq!(right[..14], [0; 14]);
+    /// # Ok::<(), gc_adpcm::ReadPlanarError>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The error contains the amount of samples per
+    /// channel that were written before it. A left sample without its right sample is
+    /// not written.
+    pub fn read_planar(
+        &mut self,
+        left: &mut [i16],
+        right: &mut [i16],
+    ) -> Result<usize, ReadPlanarError> {
+        read_planar(self, left, right)
+    }
+}
+
+impl<R: Read> Decoder<R, StereoInterleaved> {
+    /// Read the next frame of both channels.
+    fn read_next_frames(&mut self) -> Result<([u8; FRAME_SIZE], [u8; FRAME_SIZE]), std::io::Error> {
+        let mut left_frame = [0; FRAME_SIZE];
+        let mut right_frame = [0; FRAME_SIZE];
+        let Some(group) = self.group.as_mut() else {
+            read_counted(
+                &mut self.left_reader,
+                &mut left_frame,
+                &mut self.left_bytes_consumed,
+            )?;
+            read_counted(
+                &mut self.left_reader,
+                &mut right_frame,
+                &mut self.left_bytes_consumed,
+            )?;
+            return Ok((left_frame, right_frame));
+        };
+        if group.next == group.len {
+            // The last group only contains the remaining frames
+            let frames = group.frames_per_group.min(self.frames_remaing / 2);
+            let frames = usize::try_from(frames).unwrap_or_else(|_| unreachable!());
+            group.data.resize(frames * FRAME_SIZE * 2, 0);
+            read_counted(
+                &mut self.left_reader,
+                &mut group.data,
+                &mut self.left_bytes_consumed,
+            )?;
+            group.len = frames;
+            group.next = 0;
+        }
+        let left = group.next * FRAME_SIZE;
+        let right = (group.len + group.next) * FRAME_SIZE;
+        left_frame.copy_from_slice(&group.data[left..left + FRAME_SIZE]);
+        right_frame.copy_from_slice(&group.data[right..right + FRAME_SIZE]);
+        group.next += 1;
+        Ok((left_frame, right_frame))
+    }
+
+    /// Read and decode the next frame of both channels.
+    fn decode_next_frames(&mut self) -> Result<([i16; 14], [i16; 14]), std::io::Error> {
+        // Both channels are counted in the frames
+        self.last_frame_index = Some((self.frames_total - self.frames_remaing) / 2);
+        let (left_frame, right_frame) = self.read_next_frames()?;
+        let mut left = self.left_state.decode_frame(left_frame);
+        let mut right = self
+            .right_state
+            .as_mut()
+            .unwrap_or_else(|| unreachable!())
+            .decode_frame(right_frame);
+        if let Some(stats) = self.stats.as_mut() {
+            stats.record(left_frame[0], &left, self.left_state.clamp_range());
+            let right_state = self.right_state.unwrap_or_else(|| unreachable!());
+            stats.record(right_frame[0], &right, right_state.clamp_range());
+        }
+        apply_gain(&mut left, self.left_gain);
+        apply_gain(&mut right, self.right_gain);
+        self.notify_frame(&left, Some(&right));
+        self.frames_remaing = self.frames_remaing.saturating_sub(2);
+        Ok((left, right))
+    }
+
+    /// Decode the next frame of both channels into the buffer if it is empty.
+    fn fill_buffer(&mut self) -> Result<(), std::io::Error> {
+        // The frames of both channels are needed, so a single remaining frame is ignored
+        if self.buffer.is_empty() && self.frames_remaing >= 2 {
+            let (left, right) = self.decode_next_frames()?;
+            self.buffer.fill_interleaved(&left, &right);
+        }
+        Ok(())
+    }
+
+    /// Skip the next `channel_frames` frames of both channels.
+    ///
+    /// The skipped frames are still decoded so that the DSP state is correct for
+    /// the frames after it. Any samples that were already decoded are discarded.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The frames before the failing frame
+    /// are skipped.
+    pub fn skip_frames(&mut self, channel_frames: u32) -> Result<(), std::io::Error> {
+        self.buffer.clear();
+        for _ in 0..channel_frames.min(self.frames_remaing / 2) {
+            self.decode_next_frames()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the history of the DSP state of both channels.
+    ///
+    /// The new history is used starting from the next frame that is decoded, samples
+    /// that are already decoded are not affected.
+    pub fn set_history(
+        &mut self,
+        left_hist1: i16,
+        left_hist2: i16,
+        right_hist1: i16,
+        right_hist2: i16,
+    ) {
+        self.left_state.set_history(left_hist1, left_hist2);
+        self.right_state
+            .as_mut()
+            .unwrap_or_else(|| unreachable!())
+            .set_history(right_hist1, right_hist2);
+    }
+
+    /// Output the decoded frames of both channels instead of individual samples.
+    ///
+    /// Every item is the next frame of the left and right channel, with the amount of
+    /// samples per channel that are in the output. See
+    /// [`Decoder::frames`](Decoder<R, Mono>::frames) for details.
+    pub fn frames(
+        mut self,
+    ) -> impl Iterator<Item = Result<([i16; 14], [i16; 14], usize), std::io::Error>> {
+        self.buffer.clear();
+        std::iter::from_fn(move || {
+            let frames = self.next_frames(Self::decode_next_frames)?;
+            Some(frames.map(|((left, right), len)| (left, right, len)))
+        })
+    }
+
+    /// Output the samples as `(left, right)` pairs.
+    ///
+    /// If an odd number of samples was already consumed, the pairs will be misaligned.
+    pub fn pairs(self) -> impl Iterator<Item = Result<(i16, i16), std::io::Error>> {
+        pair_samples(self)
+    }
+
+    /// Output the average of the left and right channel, to get a mono audio stream.
+    ///
+    /// The average is rounded half away from zero. If an odd number of samples was already consumed, the samples of two different
+    /// positions are averaged.
+    pub fn downmix_mono(self) -> impl Iterator<Item = Result<i16, std::io::Error>> {
+        downmix(self)
+    }
+
+    /// Split the output into an iterator for the left and the right channel.
+    ///
+    /// The iterators can be consumed independently, see [`SplitChannel`]. If an odd number
+    /// of samples was already consumed, the channels will be swapped.
+    pub fn split(
+        self,
+    ) -> (
+        SplitChannel<R, StereoInterleaved>,
+        SplitChannel<R, StereoInterleaved>,
+    ) {
+        SplitChannel::new(self)
+    }
+
+    /// Decode samples into separate buffers for the left and right channel.
+    ///
+    /// Returns the amount of samples written per channel, this is at most the length
+    /// of the shortest buffer. Any remaining decoded samples are kept for the next call.
+    ///
+    /// If an odd number of samples was already consumed, the channels will be swapped.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails. The error contains the amount of samples per
+    /// channel that were written before it. A left sample without its right sample is
+    /// not written.
+    pub fn read_planar(
+        &mut self,
+        left: &mut [i16],
+        right: &mut [i16],
+    ) -> Result<usize, ReadPlanarError> {
+        read_planar(self, left, right)
+    }
+}
+
+impl<R: Read, C: Channels> Iterator for Decoder<R, C> {
+    type Item = Result<i16, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || Self::position(self) >= self.samples_total {
+            // The rest of the last frame is padding
+            return None;
+        }
+        if let Err(e) = self.fill_buffer_once() {
+            return Some(Err(e));
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+
+    // Folding over all samples of a frame at once is much faster than calling `next`
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        while !self.failed && Self::position(&self) < self.samples_total {
+            if let Err(e) = self.fill_buffer_once() {
+                acc = f(acc, Err(e));
+                break;
+            }
+            let buffered = self.buffered();
+            if buffered == 0 {
+                break;
+            }
+            for &sample in &self.buffer.as_slice()[..buffered] {
+                acc = f(acc, Ok(sample));
+            }
+            // Anything after the buffered samples is padding
+            self.buffer.clear();
+        }
+        acc
+    }
+}
+
+impl<R: Read, C: Channels> FusedIterator for Decoder<R, C> {}
+
+/// Check that `channel_frames` frames of `channels` channels in one stream are supported.
+fn check_frames(channel_frames: u32, channels: u32) -> Result<(), ConstructError> {
+    let frames = channel_frames
+        .checked_mul(channels)
+        .ok_or(ConstructError::TooManyFrames)?;
+    usize::try_from(frames)
+        .ok()
+        .and_then(|frames| frames.checked_mul(FRAME_SIZE))
+        .ok_or(ConstructError::TooManyBytes)?;
+    Ok(())
+}
+
+/// Calculate the amount of frames per channel that are left in the reader.
+///
+/// Any trailing bytes that do not make up a full frame for every channel are ignored.
+/// The position of the reader is not changed.
+fn remaining_frames<R: Seek>(reader: &mut R, channels: u64) -> Result<u32, std::io::Error> {
+    let step = u64::try_from(FRAME_SIZE).unwrap_or_else(|_| unreachable!()) * channels;
+    u32::try_from(remaining_bytes(reader)? / step).map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the reader contains too many frames",
+        )
+    })
+}
+
+/// Convert the end of a range of samples to the amount of samples to decode.
+fn range_end(start: u64, end: u64) -> Result<u32, std::io::Error> {
+    if start > end {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the start of the range is after the end",
+        ));
+    }
+    u32::try_from(end).map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the end of the range does not fit in a u32",
+        )
+    })
+}
+
+/// Calculate the amount of bytes that are left in the reader.
+///
+/// The position of the reader is not changed.
+fn remaining_bytes<R: Seek>(reader: &mut R) -> Result<u64, std::io::Error> {
+    let position = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(end.saturating_sub(position))
+}
+
+/// Remove the errors from the output of a decoder that reads from memory.
+///
+/// The amount of frames must fit in the reader, so reading never fails.
+fn infallible<C: Channels>(decoder: Decoder<Cursor<Vec<u8>>, C>) -> impl Iterator<Item = i16> {
+    decoder.map(|sample| sample.unwrap_or_else(|_| unreachable!()))
+}
+
+/// Read exactly enough bytes to fill `buf` and add the amount of bytes to `consumed`.
+///
+/// Like [`Read::read_exact`], but the bytes are also counted if the read fails halfway.
+fn read_counted<R: Read>(
+    reader: &mut R,
+    buf: &mut [u8],
+    consumed: &mut u64,
+) -> Result<(), std::io::Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+            Ok(read) => {
+                filled += read;
+                *consumed += u64::try_from(read).unwrap_or_else(|_| unreachable!());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Multiply the samples by `gain` and clamp them to the range of an [`i16`].
+fn apply_gain(samples: &mut [i16; 14], gain: Option<f32>) {
+    if let Some(gain) = gain {
+        for sample in samples {
+            #[allow(
+                clippy::as_conversions,
+                clippy::cast_possible_truncation,
+                reason = "Float to integer casts saturate, and it's clamped to i16 afterwards."
+            )]
+            let scaled = (f32::from(*sample) * gain).round() as i32;
+            *sample = clamp(scaled);
+        }
+    }
+}
+
+/// Combine the interleaved samples of `iter` into `(left, right)` pairs.
+fn pair_samples<I>(mut iter: I) -> impl Iterator<Item = Result<(i16, i16), std::io::Error>>
+where
+    I: Iterator<Item = Result<i16, std::io::Error>>,
+{
+    std::iter::from_fn(move || next_pair(&mut iter))
+}
+
+/// Average the interleaved samples of `iter` to get a single channel.
+fn downmix<I>(iter: I) -> impl Iterator<Item = Result<i16, std::io::Error>>
+where
+    I: Iterator<Item = Result<i16, std::io::Error>>,
+{
+    pair_samples(iter).map(|pair| pair.map(|(left, right)| average(left, right)))
+}
+
+/// Average two samples, rounding half away from zero.
+fn average(left: i16, right: i16) -> i16 {
+    let sum = i32::from(left) + i32::from(right);
+    // The average of two samples is always in the range of an i16
+    i16::try_from((sum + sum.signum()) / 2).unwrap_or_else(|_| unreachable!())
+}
+
+/// Get the next `(left, right)` pair from the interleaved samples of `iter`.
+///
+/// If getting the right sample fails, the left sample is discarded. The decoders end after
+/// an error, so there is no next pair for it to be part of.
+fn next_pair<I>(iter: &mut I) -> Option<Result<(i16, i16), std::io::Error>>
+where
+    I: Iterator<Item = Result<i16, std::io::Error>>,
+{
+    let left = match iter.next()? {
+        Ok(sample) => sample,
+        Err(e) => return Some(Err(e)),
+    };
+    Some(iter.next()?.map(|right| (left, right)))
+}
+
+/// Decode samples from `decoder` into separate buffers for the left and right channel.
+fn read_planar<R: Read, C: Channels>(
+    decoder: &mut Decoder<R, C>,
+    left: &mut [i16],
+    right: &mut [i16],
+) -> Result<usize, ReadPlanarError> {
+    let mut written = 0;
+    for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+        match next_pair(decoder) {
+            Some(Ok((l, r))) => {
+                *left = l;
+                *right = r;
+                written += 1;
+            }
+            Some(Err(error)) => return Err(ReadPlanarError { written, error }),
+            None => break,
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    //! Behavioural tests for [`Decoder`].
+    use super::*;
+    use crate::test_util::{frames, Broken, FRAME};
+    use std::io::{Cursor, SeekFrom};
+
+    /// Decoders without frames or samples never read from the reader(s).
+    #[test]
+    fn empty_decoders_do_not_read() {
+        let state = Dsp::default();
+        let mut decoders: Vec<Box<dyn Iterator<Item = Result<i16, std::io::Error>>>> = vec![
+            Box::new(Decoder::mono(Broken, state, 0)),
+            Box::new(Decoder::mono_samples(Broken, state, 0)),
+            Box::new(Decoder::stereo(Broken, state, Broken, state, 0)),
+            Box::new(Decoder::stereo_samples(Broken, state, Broken, state, 0)),
+            Box::new(Decoder::interleaved_stereo(Broken, state, state, 0)),
+            Box::new(Decoder::interleaved_stereo_samples(Broken, state, state, 0)),
+        ];
+        for decoder in &mut decoders {
+            assert!(decoder.next().is_none(), "Empty decoder returned a sample");
+        }
+    }
+
+    /// A read error is returned once after the complete frames, and then the decoder ends.
+    #[test]
+    fn read_error_ends_decoder() {
+        for count in 0..3 {
+            // The reader fails after `count` frames, and halfway through the next frame
+            let data = [frames(count), FRAME[..4].to_vec()].concat();
+            let mut decoder = Decoder::mono(Cursor::new(data).chain(Broken), Dsp::default(), 5);
+            let samples = decoder.by_ref().take_while(Result::is_ok).count();
+            assert_eq!(samples, count * 14, "Partial frame was returned");
+            assert!(decoder.next().is_none(), "Decoder continued after an error");
+        }
+
+        let reader = Cursor::new([0; 20]).chain(Broken);
+        let mut decoder = Decoder::interleaved_stereo(reader, Dsp::default(), Dsp::default(), 2);
+        assert!(
+            decoder.next().is_some_and(|sample| sample.is_ok()),
+            "First step failed"
+        );
+        let errors = decoder.by_ref().filter(Result::is_err).count();
+        assert_eq!(errors, 1, "Error was not returned exactly once");
+        assert!(decoder.next().is_none(), "Decoder continued after an error");
+    }
+
+    /// The `*_samples` constructors return exactly the requested amount of samples.
+    #[test]
+    fn sample_counts() {
+        let data = frames(4);
+        let state = Dsp::default();
+        for samples in [0, 1, 13, 14, 15, 27, 28] {
+            let expected = usize::try_from(samples).unwrap_or_else(|_| unreachable!());
+            let mono = Decoder::mono_samples(Cursor::new(&data), state, samples);
+            assert_eq!(mono.count(), expected, "Wrong mono sample count");
+            let (left, right) = (Cursor::new(&data), Cursor::new(&data));
+            let stereo = Decoder::stereo_samples(left, state, right, state, samples);
+            assert_eq!(
+                stereo.pairs().count(),
+                expected,
+                "Wrong stereo sample count"
+            );
+            let interleaved =
+                Decoder::interleaved_stereo_samples(Cursor::new(&data), state, state, samples);
+            assert_eq!(
+                interleaved.pairs().count(),
+                expected,
+                "Wrong interleaved sample count"
+            );
+        }
+    }
+
+    /// A file without a valid header is rejected.
+    #[test]
+    fn from_dsp_file_invalid() {
+        let result = Decoder::from_dsp_file(Cursor::new([0xFF; 0x60]));
+        assert!(
+            result.is_err_and(|e| e.kind() == std::io::ErrorKind::InvalidData),
+            "Invalid header was accepted"
+        );
+    }
+
+    /// Stereo output is the same as decoding both channels separately and interleaving them.
+    #[test]
+    fn stereo_matches_separate_channels() -> Result<(), std::io::Error> {
+        let coefficients = [1024, -512, 2048, -1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let left: Vec<u8> = (0..1000u32).map(|i| (i * 37).to_le_bytes()[0]).collect();
+        let right: Vec<u8> = (0..1000u32).map(|i| (i * 91).to_le_bytes()[0]).collect();
+        let state = Dsp::from_coefficients(coefficients);
+        let decoded = Decoder::stereo(Cursor::new(&left), state, Cursor::new(&right), state, 125)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (mut left_state, mut right_state) = (state, state);
+        let mut expected = Vec::new();
+        for (left, right) in left.chunks_exact(8).zip(right.chunks_exact(8)) {
+            let left = left_state
+                .decode_frame_slice(left)
+                .unwrap_or_else(|_| unreachable!());
+            let right = right_state
+                .decode_frame_slice(right)
+                .unwrap_or_else(|_| unreachable!());
+            expected.extend(left.into_iter().zip(right).flat_map(<[i16; 2]>::from));
+        }
+        assert_eq!(decoded, expected, "Stereo output does not match");
+        Ok(())
+    }
+
+    /// The frame count of interleaved stereo is limited by the count of both channels.
+    #[test]
+    fn try_interleaved_stereo_limit() {
+        let state = Dsp::default();
+        let result = Decoder::try_interleaved_stereo(Broken, state, state, u32::MAX / 2);
+        assert!(result.is_ok(), "Largest frame count was rejected");
+        let result = Decoder::try_interleaved_stereo(Broken, state, state, u32::MAX / 2 + 1);
+        assert!(
+            matches!(result, Err(ConstructError::TooManyFrames)),
+            "Frame count should be too large"
+        );
+    }
+
+    /// Seeking with fewer warmup frames estimates the history, and seeking to the start
+    /// restores the initial history.
+    #[test]
+    fn seek_to_sample_warmup_history() -> Result<(), std::io::Error> {
+        let coefficients = [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let data = [0x03, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(4);
+        let state = Dsp::new(coefficients, 1000, -500);
+        let mut decoder = Decoder::mono(Cursor::new(data), state, 4);
+        let all: Vec<i16> = decoder.by_ref().collect::<Result<_, _>>()?;
+
+        decoder.seek_to_sample_warmup(45, 1)?;
+        assert_eq!(
+            decoder.by_ref().count(),
+            11,
+            "Wrong amount of samples after seek"
+        );
+
+        decoder.seek_to_sample_warmup(0, 0)?;
+        let samples = decoder.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(samples, all, "Initial history was not restored");
+        Ok(())
+    }
+
+    /// Seeking works for a stream in a container, after a read failed halfway through a frame.
+    #[test]
+    fn seek_to_sample_warmup_after_error() -> Result<(), std::io::Error> {
+        // Four bytes of a container, two frames, and half of the third frame
+        let data = [&[0xFF; 4][..], &frames(2), &[0; 4]].concat();
+        let mut reader = Cursor::new(data);
+        reader.seek(SeekFrom::Start(4))?;
+        let mut decoder = Decoder::mono(reader, Dsp::default(), 3);
+        let all: Vec<_> = decoder.by_ref().collect();
+        assert!(all[28].is_err(), "Truncated frame was decoded");
+
+        decoder.seek_to_sample_warmup(14, 0)?;
+        let frame: Vec<i16> = decoder.by_ref().take(14).collect::<Result<_, _>>()?;
+        let expected: Vec<i16> = all[14..28].iter().flatten().copied().collect();
+        assert_eq!(frame, expected, "Wrong samples after seek");
+        Ok(())
+    }
+
+    /// Seeking is refused after segments are chained, also after they are decoded.
+    #[test]
+    fn seek_chained() {
+        let mut decoder = Decoder::mono(Cursor::new(frames(1)), Dsp::default(), 1)
+            .chain(Cursor::new(frames(1)), 1);
+        for _ in 0..2 {
+            let result = decoder.seek_to_sample_warmup(0, 0);
+            assert!(
+                result.is_err_and(|e| e.kind() == std::io::ErrorKind::Unsupported),
+                "Seeking a chained decoder was accepted"
+            );
+            decoder.by_ref().for_each(drop);
+        }
+    }
+
+    /// Seeking fails instead of overflowing if the reader was moved before the stream.
+    #[test]
+    fn seek_reader_before_stream() {
+        let mut decoder = Decoder::mono(Cursor::new(frames(2)), Dsp::default(), 2);
+        decoder.by_ref().take(14).for_each(drop);
+        decoder.left_reader.set_position(0);
+        let result = decoder.seek_to_sample_warmup(0, 0);
+        assert!(
+            result.is_err_and(|e| e.kind() == std::io::ErrorKind::InvalidInput),
+            "Reader before the stream was accepted"
+        );
+    }
+
+    /// A range that starts after its end is rejected.
+    #[test]
+    fn range_start_after_end() {
+        let invalid = |e: std::io::Error| e.kind() == std::io::ErrorKind::InvalidInput;
+        let range = Decoder::mono_range(Cursor::new(frames(2)), Dsp::default(), 20, 10);
+        assert!(range.is_err_and(invalid), "Range was accepted");
+        let range = Decoder::mono_range_seek(Cursor::new(frames(2)), Dsp::default(), 20, 10);
+        assert!(range.is_err_and(invalid), "Seeking range was accepted");
+    }
+
+    /// [`Decoder::frames`] returns an error once, and then ends.
+    #[test]
+    fn frames_read_error() {
+        let reader = Cursor::new(FRAME).chain(Broken);
+        let mut decoded = Decoder::mono(reader, Dsp::default(), 3).frames();
+        assert!(
+            decoded.next().is_some_and(|frame| frame.is_ok()),
+            "First frame failed"
+        );
+        assert!(
+            decoded.next().is_some_and(|frame| frame.is_err()),
+            "Error was not returned"
+        );
+        assert!(decoded.next().is_none(), "Frames continued after an error");
+    }
+
+    /// The trimmed frames of both channels contain the same samples as the iterator.
+    #[test]
+    fn stereo_frames_match_pairs() -> Result<(), std::io::Error> {
+        let data = frames(4);
+        let state = Dsp::default();
+        let pairs = Decoder::interleaved_stereo_samples(Cursor::new(&data), state, state, 20)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut decoded = Vec::new();
+        for frame in
+            Decoder::interleaved_stereo_samples(Cursor::new(&data), state, state, 20).frames()
+        {
+            let (left, right, len) = frame?;
+            decoded.extend(left.into_iter().zip(right).take(len));
+        }
+        assert_eq!(decoded, pairs, "Frames differ from the pairs");
+        Ok(())
+    }
+
+    /// The downmix rounds odd sums of both signs half away from zero.
+    #[test]
+    fn downmix_rounding() -> Result<(), std::io::Error> {
+        // Every nibble is the sample, as the scale is 1 and the coefficients are 0
+        let left = [0x00, 0x77, 0x99, 0x77, 0x99, 0x00, 0x00, 0x00];
+        let right = [0x00, 0x22, 0xEE, 0xEE, 0x22, 0x00, 0x00, 0x00];
+        let decoder = Decoder::stereo(
+            Cursor::new(left),
+            Dsp::default(),
+            Cursor::new(right),
+            Dsp::default(),
+            1,
+        );
+        let samples = decoder.downmix_mono().collect::<Result<Vec<_>, _>>()?;
+        // 7 + 2, -7 - 2, 7 - 2, and -7 + 2, and 0 + 0
+        assert_eq!(
+            samples[..10],
+            [5, 5, -5, -5, 3, 3, -3, -3, 0, 0],
+            "Average is not rounded half away from zero"
+        );
+
+        let extremes = [
+            (i16::MAX, i16::MAX),
+            (i16::MIN, i16::MIN),
+            (i16::MAX, i16::MIN),
+        ];
+        let averages = extremes.map(|(left, right)| average(left, right));
+        assert_eq!(
+            averages,
+            [i16::MAX, i16::MIN, -1],
+            "Extremes are not averaged"
+        );
+        Ok(())
+    }
+
+    /// [`Decoder::read_planar`] reports the samples written before the error.
+    #[test]
+    fn read_planar_error() {
+        // The second frame of the right channel is missing
+        let left = Cursor::new(vec![0; 16]);
+        let right = Cursor::new(vec![0; 8]);
+        let mut decoder = Decoder::stereo(left, Dsp::default(), right, Dsp::default(), 2);
+        decoder.next();
+        let (mut left, mut right) = ([0; 20], [0; 20]);
+        let Err(error) = decoder.read_planar(&mut left, &mut right) else {
+            panic!("Missing frame was not an error");
+        };
+        // The last sample of the first frames has no sample to pair with
+        assert_eq!(error.written, 13, "Wrong amount of samples written");
+        assert!(decoder.next().is_none(), "Decoder continued after an error");
     }
 }
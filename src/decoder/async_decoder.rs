@@ -0,0 +1,269 @@
+//! An async decoder that takes a `tokio::io::AsyncRead` and outputs `i16`.
+use super::Decoder;
+use crate::{Channels, Dsp, Mono, Stereo, StereoInterleaved, FRAME_SIZE};
+use std::io::Cursor;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Reader for the frames of one step, which are read asynchronously before they are decoded.
+type StepReader = Cursor<[u8; 2 * FRAME_SIZE]>;
+
+/// Async version of [`Decoder`].
+///
+/// It takes the initial DSP state and one or two readers for the stream data and
+/// outputs `Result<i16, std::io::Error>` via [`AsyncDecoder::next_sample`].
+///
+/// Only reading is asynchronous, the frames are decoded by a [`Decoder`]. The output is
+/// therefore identical, including stopping after the first error.
+///
+/// ```
+/// use gc_adpcm::{AsyncDecoder, Dsp};
+///
+/// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+/// let mut decoder = AsyncDecoder::mono_samples(&frames[..], Dsp::default(), 20);
+/// let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+/// let mut samples = Vec::new();
+/// while let Some(sample) = runtime.block_on(decoder.next_sample()) {
+///     samples.push(sample?);
+/// }
+/// assert_eq!(samples.len(), 20);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct AsyncDecoder<R: AsyncRead + Unpin, C: Channels> {
+    /// The reader for the left/mono/interleaved audio stream
+    left_reader: R,
+    /// The reader for the right channel audio stream, only available on [`Stereo`]
+    right_reader: Option<R>,
+    /// The decoder for the frames that are read
+    decoder: Decoder<StepReader, C>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R, Mono> {
+    /// Decode a mono audio stream.
+    ///
+    /// `frames` is the amount of frames in the channel.
+    pub fn mono(reader: R, state: Dsp, frames: u32) -> Self {
+        Self {
+            left_reader: reader,
+            right_reader: None,
+            decoder: Decoder::mono(StepReader::default(), state, frames),
+        }
+    }
+
+    /// Decode a mono audio stream.
+    ///
+    /// `samples` is the amount of samples in the channel.
+    pub fn mono_samples(reader: R, state: Dsp, samples: u32) -> Self {
+        Self {
+            left_reader: reader,
+            right_reader: None,
+            decoder: Decoder::mono_samples(StepReader::default(), state, samples),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R, Stereo> {
+    /// Decode a stereo audio stream where each channel has their own buffer.
+    ///
+    /// `channel_frames` is the amount of frames in *one* channel.
+    pub fn stereo(
+        left_reader: R,
+        left_state: Dsp,
+        right_reader: R,
+        right_state: Dsp,
+        channel_frames: u32,
+    ) -> Self {
+        Self {
+            left_reader,
+            right_reader: Some(right_reader),
+            decoder: Decoder::stereo(
+                StepReader::default(),
+                left_state,
+                StepReader::default(),
+                right_state,
+                channel_frames,
+            ),
+        }
+    }
+
+    /// Decode a stereo audio stream where each channel has their own buffer.
+    ///
+    /// `channel_samples` is the amount of samples in *one* channel.
+    pub fn stereo_samples(
+        left_reader: R,
+        left_state: Dsp,
+        right_reader: R,
+        right_state: Dsp,
+        channel_samples: u32,
+    ) -> Self {
+        Self {
+            left_reader,
+            right_reader: Some(right_reader),
+            decoder: Decoder::stereo_samples(
+                StepReader::default(),
+                left_state,
+                StepReader::default(),
+                right_state,
+                channel_samples,
+            ),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R, StereoInterleaved> {
+    /// Decode a stereo audio stream interleaved per frame.
+    ///
+    /// `channel_frames` is the amount of frames in *one* channel.
+    pub fn interleaved_stereo(
+        reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+        channel_frames: u32,
+    ) -> Self {
+        Self {
+            left_reader: reader,
+            right_reader: None,
+            decoder: Decoder::interleaved_stereo(
+                StepReader::default(),
+                left_state,
+                right_state,
+                channel_frames,
+            ),
+        }
+    }
+
+    /// Decode a stereo audio stream interleaved per frame.
+    ///
+    /// `channel_samples` is the amount of samples in *one* channel.
+    pub fn interleaved_stereo_samples(
+        reader: R,
+        left_state: Dsp,
+        right_state: Dsp,
+        channel_samples: u32,
+    ) -> Self {
+        Self {
+            left_reader: reader,
+            right_reader: None,
+            decoder: Decoder::interleaved_stereo_samples(
+                StepReader::default(),
+                left_state,
+                right_state,
+                channel_samples,
+            ),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin, C: Channels> AsyncDecoder<R, C> {
+    /// Decode the next sample.
+    ///
+    /// Returns `None` when all frames are decoded, or after an error was returned. For
+    /// stereo audio streams the samples are interleaved per sample.
+    pub async fn next_sample(&mut self) -> Option<Result<i16, std::io::Error>> {
+        if self.decoder.needs_frames() {
+            if let Err(e) = self.read_step().await {
+                self.decoder.failed = true;
+                return Some(Err(e));
+            }
+        }
+        self.decoder.next()
+    }
+
+    /// Read the frames of the next step into the readers of the decoder.
+    async fn read_step(&mut self) -> Result<(), std::io::Error> {
+        let frames = usize::try_from(C::FRAMES_PER_STEP).unwrap_or_else(|_| unreachable!());
+        read_frames(
+            &mut self.left_reader,
+            &mut self.decoder.left_reader,
+            frames * FRAME_SIZE,
+        )
+        .await?;
+        if let (Some(reader), Some(step)) = (
+            self.right_reader.as_mut(),
+            self.decoder.right_reader.as_mut(),
+        ) {
+            read_frames(reader, step, FRAME_SIZE).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Read `len` bytes from `reader` into `step`, and rewind `step` to the start.
+async fn read_frames<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    step: &mut StepReader,
+    len: usize,
+) -> Result<(), std::io::Error> {
+    reader.read_exact(&mut step.get_mut()[..len]).await?;
+    step.set_position(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests comparing [`AsyncDecoder`] with [`Decoder`].
+    use super::*;
+    use crate::test_util::Broken;
+    use std::io::ErrorKind;
+
+    /// Get all samples of `decoder`, with the errors replaced by their kind.
+    async fn collect<R: AsyncRead + Unpin, C: Channels>(
+        mut decoder: AsyncDecoder<R, C>,
+    ) -> Vec<Result<i16, ErrorKind>> {
+        let mut samples = Vec::new();
+        while let Some(sample) = decoder.next_sample().await {
+            samples.push(sample.map_err(|e| e.kind()));
+        }
+        samples
+    }
+
+    /// Get all samples of `decoder`, with the errors replaced by their kind.
+    fn collect_sync<R: std::io::Read, C: Channels>(
+        decoder: Decoder<R, C>,
+    ) -> Vec<Result<i16, ErrorKind>> {
+        decoder.map(|sample| sample.map_err(|e| e.kind())).collect()
+    }
+
+    /// The output is identical to [`Decoder`], also when the reader fails.
+    #[test]
+    fn matches_decoder() -> Result<(), std::io::Error> {
+        let state =
+            Dsp::from_coefficients([1024, -512, 2048, -1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let data: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(37)).collect();
+        let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+        // The data is cut off at different places, after which the reader fails
+        for len in [0, 20, 32, 40, 64, 100] {
+            let sync = || std::io::Read::chain(&data[..len], Broken);
+            let r#async = || AsyncReadExt::chain(&data[..len], Broken);
+
+            let expected = collect_sync(Decoder::mono_samples(sync(), state, 50));
+            let samples =
+                runtime.block_on(collect(AsyncDecoder::mono_samples(r#async(), state, 50)));
+            assert_eq!(samples, expected, "Mono output differs");
+
+            let expected = collect_sync(Decoder::stereo_samples(sync(), state, sync(), state, 50));
+            let samples = runtime.block_on(collect(AsyncDecoder::stereo_samples(
+                r#async(),
+                state,
+                r#async(),
+                state,
+                50,
+            )));
+            assert_eq!(samples, expected, "Stereo output differs");
+
+            let expected = collect_sync(Decoder::interleaved_stereo_samples(
+                sync(),
+                state,
+                state,
+                50,
+            ));
+            let samples = runtime.block_on(collect(AsyncDecoder::interleaved_stereo_samples(
+                r#async(),
+                state,
+                state,
+                50,
+            )));
+            assert_eq!(samples, expected, "Interleaved output differs");
+        }
+        Ok(())
+    }
+}
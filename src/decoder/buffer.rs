@@ -0,0 +1,79 @@
+//! A buffer for the decoded samples of one frame of every channel, stored inline.
+
+/// The maximum amount of samples in the buffer, one frame of two channels
+const CAPACITY: usize = 28;
+
+/// Buffer for the decoded samples of one frame of every channel.
+///
+/// The samples are stored inline, so no allocation is needed. The buffer is only filled
+/// when it is empty, so the capacity is never exceeded.
+#[derive(Clone)]
+pub struct FrameBuffer {
+    /// The decoded samples
+    samples: [i16; CAPACITY],
+    /// The index of the next sample that is returned
+    start: usize,
+    /// The index after the last sample in the buffer
+    end: usize,
+}
+
+impl FrameBuffer {
+    /// Create an empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            samples: [0; CAPACITY],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Replace the contents of the buffer with the samples of one channel.
+    pub fn fill(&mut self, samples: &[i16; 14]) {
+        self.samples[..14].copy_from_slice(samples);
+        self.start = 0;
+        self.end = 14;
+    }
+
+    /// Replace the contents of the buffer with the samples of both channels, interleaved
+    /// per sample starting with the left channel.
+    pub fn fill_interleaved(&mut self, left: &[i16; 14], right: &[i16; 14]) {
+        for ((out, &left), &right) in self.samples.chunks_exact_mut(2).zip(left).zip(right) {
+            out.copy_from_slice(&[left, right]);
+        }
+        self.start = 0;
+        self.end = CAPACITY;
+    }
+
+    /// Check if there are no samples in the buffer.
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The amount of samples in the buffer.
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// The samples in the buffer.
+    pub fn as_slice(&self) -> &[i16] {
+        &self.samples[self.start..self.end]
+    }
+
+    /// Remove all samples from the buffer.
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.end = 0;
+    }
+
+    /// Get the next sample without removing it.
+    pub fn front(&self) -> Option<i16> {
+        (!self.is_empty()).then(|| self.samples[self.start])
+    }
+
+    /// Remove and return the next sample.
+    pub fn pop_front(&mut self) -> Option<i16> {
+        let sample = self.front()?;
+        self.start += 1;
+        Some(sample)
+    }
+}
@@ -0,0 +1,370 @@
+//! A builder for [`Decoder`].
+use crate::{Decoder, Dsp, LoopingDecoder, Mono, Stereo, StereoInterleaved};
+use std::io::{Read, Seek};
+
+/// The amount of frames or samples in a channel
+#[derive(Clone, Copy)]
+enum Count {
+    /// The amount of frames in a channel
+    Frames(u32),
+    /// The amount of samples in a channel
+    Samples(u32),
+}
+
+/// Error returned when building a [`Decoder`] with a [`DecoderBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// A field that is required for this channel layout is not set
+    MissingField(&'static str),
+    /// A field is set that is not used by this channel layout
+    UnusedField(&'static str),
+    /// The loop start is not before the loop end
+    InvalidLoopPoints,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "`{field}` is required but not set"),
+            Self::UnusedField(field) => {
+                write!(f, "`{field}` is set but not used for this channel layout")
+            }
+            Self::InvalidLoopPoints => write!(f, "the loop start is not before the loop end"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Builder for a [`Decoder`].
+///
+/// For [`Mono`] and [`StereoInterleaved`] only [`DecoderBuilder::reader`] is used, for
+/// [`Stereo`] [`DecoderBuilder::right_reader`] is also needed. The same goes for the state
+/// and the gain. If [`DecoderBuilder::loop_points`] is set, one of the `build_looping_*`
+/// methods needs to be used to build a [`LoopingDecoder`].
+///
+/// ```
+/// use gc_adpcm::{DecoderBuilder, Dsp};
+/// use std::io::Cursor;
+///
+/// let frames = [0; 16];
+/// let decoder = DecoderBuilder::new()
+///     .reader(Cursor::new(frames))
+///     .state(Dsp::default())
+///     .right_state(Dsp::default())
+///     .frames(1)
+///     .gain(0.5)
+///     .right_gain(2.0)
+///     .build_interleaved()?;
+/// assert_eq!(decoder.count(), 28);
+/// # Ok::<(), gc_adpcm::BuildError>(())
+/// ```
+pub struct DecoderBuilder<R: Read> {
+    /// The reader for the left/mono/interleaved audio stream
+    left_reader: Option<R>,
+    /// The reader for the right channel audio stream
+    right_reader: Option<R>,
+    /// The DSP state of the left/mono channel
+    left_state: Option<Dsp>,
+    /// The DSP state of the right channel
+    right_state: Option<Dsp>,
+    /// The amount of frames or samples in one channel
+    count: Option<Count>,
+    /// The gain applied to the left/mono channel
+    left_gain: Option<f32>,
+    /// The gain applied to the right channel
+    right_gain: Option<f32>,
+    /// The loop start and loop end in samples of one channel
+    loop_points: Option<(u32, u32)>,
+}
+
+impl<R: Read> Default for DecoderBuilder<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Read> DecoderBuilder<R> {
+    /// Create a builder with nothing set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            left_reader: None,
+            right_reader: None,
+            left_state: None,
+            right_state: None,
+            count: None,
+            left_gain: None,
+            right_gain: None,
+            loop_points: None,
+        }
+    }
+
+    /// Set the reader for the left/mono/interleaved audio stream.
+    #[must_use]
+    pub fn reader(mut self, reader: R) -> Self {
+        self.left_reader = Some(reader);
+        self
+    }
+
+    /// Set the reader for the right channel audio stream, only used for [`Stereo`].
+    #[must_use]
+    pub fn right_reader(mut self, reader: R) -> Self {
+        self.right_reader = Some(reader);
+        self
+    }
+
+    /// Set the DSP state of the left/mono channel.
+    #[must_use]
+    pub const fn state(mut self, state: Dsp) -> Self {
+        self.left_state = Some(state);
+        self
+    }
+
+    /// Set the DSP state of the right channel, not used for [`Mono`].
+    #[must_use]
+    pub const fn right_state(mut self, state: Dsp) -> Self {
+        self.right_state = Some(state);
+        self
+    }
+
+    /// Set the amount of frames in *one* channel.
+    #[must_use]
+    pub const fn frames(mut self, channel_frames: u32) -> Self {
+        self.count = Some(Count::Frames(channel_frames));
+        self
+    }
+
+    /// Set the amount of samples in *one* channel.
+    #[must_use]
+    pub const fn samples(mut self, channel_samples: u32) -> Self {
+        self.count = Some(Count::Samples(channel_samples));
+        self
+    }
+
+    /// Set the gain of the left/mono channel.
+    ///
+    /// See [`Decoder::with_gain`] for details.
+    #[must_use]
+    pub const fn gain(mut self, gain: f32) -> Self {
+        self.left_gain = Some(gain);
+        self
+    }
+
+    /// Set the gain of the right channel, not used for [`Mono`].
+    ///
+    /// See [`Decoder::with_gain`] for details.
+    #[must_use]
+    pub const fn right_gain(mut self, gain: f32) -> Self {
+        self.right_gain = Some(gain);
+        self
+    }
+
+    /// Set the loop points in samples of *one* channel, only used by the `build_looping_*`
+    /// methods.
+    ///
+    /// See [`LoopingDecoder::new`] for details.
+    #[must_use]
+    pub const fn loop_points(mut self, loop_start: u32, loop_end: u32) -> Self {
+        self.loop_points = Some((loop_start, loop_end));
+        self
+    }
+
+    /// Build a decoder for a mono audio stream.
+    ///
+    /// # Errors
+    /// Returns an error if the reader, state, or count is missing, or if a field for
+    /// the right channel or the loop points are set.
+    pub fn build_mono(self) -> Result<Decoder<R, Mono>, BuildError> {
+        check_unused(self.loop_points.is_some(), "loop_points")?;
+        check_unused(self.right_reader.is_some(), "right_reader")?;
+        check_unused(self.right_state.is_some(), "right_state")?;
+        check_unused(self.right_gain.is_some(), "right_gain")?;
+        let reader = self.left_reader.ok_or(BuildError::MissingField("reader"))?;
+        let state = self.left_state.ok_or(BuildError::MissingField("state"))?;
+        let mut decoder = match self
+            .count
+            .ok_or(BuildError::MissingField("frames or samples"))?
+        {
+            Count::Frames(frames) => Decoder::mono(reader, state, frames),
+            Count::Samples(samples) => Decoder::mono_samples(reader, state, samples),
+        };
+        decoder.left_gain = self.left_gain;
+        Ok(decoder)
+    }
+
+    /// Build a decoder for a stereo audio stream where each channel has their own buffer.
+    ///
+    /// # Errors
+    /// Returns an error if a reader, state, or the count is missing, or if the loop points
+    /// are set.
+    pub fn build_stereo(self) -> Result<Decoder<R, Stereo>, BuildError> {
+        check_unused(self.loop_points.is_some(), "loop_points")?;
+        let left_reader = self.left_reader.ok_or(BuildError::MissingField("reader"))?;
+        let right_reader = self
+            .right_reader
+            .ok_or(BuildError::MissingField("right_reader"))?;
+        let left_state = self.left_state.ok_or(BuildError::MissingField("state"))?;
+        let right_state = self
+            .right_state
+            .ok_or(BuildError::MissingField("right_state"))?;
+        let mut decoder = match self
+            .count
+            .ok_or(BuildError::MissingField("frames or samples"))?
+        {
+            Count::Frames(frames) => {
+                Decoder::stereo(left_reader, left_state, right_reader, right_state, frames)
+            }
+            Count::Samples(samples) => {
+                Decoder::stereo_samples(left_reader, left_state, right_reader, right_state, samples)
+            }
+        };
+        decoder.left_gain = self.left_gain;
+        decoder.right_gain = self.right_gain;
+        Ok(decoder)
+    }
+
+    /// Build a decoder for a stereo audio stream interleaved per frame.
+    ///
+    /// # Errors
+    /// Returns an error if the reader, a state, or the count is missing, or if the right
+    /// reader or the loop points are set.
+    pub fn build_interleaved(self) -> Result<Decoder<R, StereoInterleaved>, BuildError> {
+        check_unused(self.loop_points.is_some(), "loop_points")?;
+        check_unused(self.right_reader.is_some(), "right_reader")?;
+        let reader = self.left_reader.ok_or(BuildError::MissingField("reader"))?;
+        let left_state = self.left_state.ok_or(BuildError::MissingField("state"))?;
+        let right_state = self
+            .right_state
+            .ok_or(BuildError::MissingField("right_state"))?;
+        let mut decoder = match self
+            .count
+            .ok_or(BuildError::MissingField("frames or samples"))?
+        {
+            Count::Frames(frames) => {
+                Decoder::interleaved_stereo(reader, left_state, right_state, frames)
+            }
+            Count::Samples(samples) => {
+                Decoder::interleaved_stereo_samples(reader, left_state, right_state, samples)
+            }
+        };
+        decoder.left_gain = self.left_gain;
+        decoder.right_gain = self.right_gain;
+        Ok(decoder)
+    }
+}
+
+impl<R: Read + Seek> DecoderBuilder<R> {
+    /// Build a decoder that loops a mono audio stream.
+    ///
+    /// ```
+    /// use gc_adpcm::{BuildError, DecoderBuilder, Dsp};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let builder = DecoderBuilder::new()
+    ///     .reader(Cursor::new(frames))
+    ///     .state(Dsp::default())
+    ///     .frames(2);
+    /// let looping = builder.loop_points(14, 28).build_looping_mono()?;
+    /// let samples: Vec<i16> = looping.take(56).collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(samples[14..28], samples[42..56]);
+    /// # Ok::<(), BuildError>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the reader, state, count, or loop points are missing, if a field
+    /// for the right channel is set, or if the loop start is not before the loop end.
+    pub fn build_looping_mono(mut self) -> Result<LoopingDecoder<R, Mono>, BuildError> {
+        let (loop_start, loop_end) = take_loop_points(&mut self.loop_points)?;
+        Ok(LoopingDecoder::new(
+            self.build_mono()?,
+            loop_start,
+            loop_end,
+        ))
+    }
+
+    /// Build a decoder that loops a stereo audio stream where each channel has their own
+    /// buffer.
+    ///
+    /// # Errors
+    /// Returns an error if a reader, state, the count, or the loop points are missing, or
+    /// if the loop start is not before the loop end.
+    pub fn build_looping_stereo(mut self) -> Result<LoopingDecoder<R, Stereo>, BuildError> {
+        let (loop_start, loop_end) = take_loop_points(&mut self.loop_points)?;
+        Ok(LoopingDecoder::new(
+            self.build_stereo()?,
+            loop_start,
+            loop_end,
+        ))
+    }
+
+    /// Build a decoder that loops a stereo audio stream interleaved per frame.
+    ///
+    /// # Errors
+    /// Returns an error if the reader, a state, the count, or the loop points are missing,
+    /// if the right reader is set, or if the loop start is not before the loop end.
+    pub fn build_looping_interleaved(
+        mut self,
+    ) -> Result<LoopingDecoder<R, StereoInterleaved>, BuildError> {
+        let (loop_start, loop_end) = take_loop_points(&mut self.loop_points)?;
+        Ok(LoopingDecoder::new(
+            self.build_interleaved()?,
+            loop_start,
+            loop_end,
+        ))
+    }
+}
+
+/// Take the loop points out of the builder and check that they are valid.
+fn take_loop_points(loop_points: &mut Option<(u32, u32)>) -> Result<(u32, u32), BuildError> {
+    let (loop_start, loop_end) = loop_points
+        .take()
+        .ok_or(BuildError::MissingField("loop_points"))?;
+    if loop_start >= loop_end {
+        return Err(BuildError::InvalidLoopPoints);
+    }
+    Ok((loop_start, loop_end))
+}
+
+/// Return [`BuildError::UnusedField`] for `field` if `is_set` is true.
+const fn check_unused(is_set: bool, field: &'static str) -> Result<(), BuildError> {
+    if is_set {
+        Err(BuildError::UnusedField(field))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Error tests for [`DecoderBuilder`].
+    use super::*;
+    use crate::test_util::frames;
+    use std::io::Cursor;
+
+    /// Invalid loop points and a missing count are rejected.
+    #[test]
+    fn build_looping_mono_errors() {
+        let builder = DecoderBuilder::new()
+            .reader(Cursor::new(frames(2)))
+            .state(Dsp::default())
+            .frames(2);
+        let result = builder.loop_points(28, 14).build_looping_mono();
+        assert_eq!(
+            result.err(),
+            Some(BuildError::InvalidLoopPoints),
+            "Loop start after loop end was accepted"
+        );
+
+        let builder = DecoderBuilder::new()
+            .reader(Cursor::new(frames(2)))
+            .state(Dsp::default());
+        let result = builder.loop_points(14, 28).build_looping_mono();
+        assert_eq!(
+            result.err(),
+            Some(BuildError::MissingField("frames or samples")),
+            "Missing count was accepted"
+        );
+    }
+}
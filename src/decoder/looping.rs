@@ -0,0 +1,160 @@
+//! A decoder that repeats a section of an audio stream.
+use super::{Channels, Decoder, DecoderState};
+use crate::util::clamp;
+use std::io::{Read, Seek};
+use std::iter::FusedIterator;
+
+/// Decoder that keeps repeating the section between the loop points.
+///
+/// The state of the decoder at the loop start is saved with [`Decoder::snapshot`] and
+/// restored at the loop end, so the history is correct and the output is bit-exact.
+/// The iterator only ends if the loop end is past the end of the audio stream, or if an
+/// error occurs.
+///
+/// ```
+/// use gc_adpcm::{Decoder, Dsp, LoopingDecoder};
+/// use std::io::Cursor;
+///
+/// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+/// let decoder = Decoder::mono(Cursor::new(frames), Dsp::default(), 2);
+/// let looping = LoopingDecoder::new(decoder, 14, 28);
+/// let samples: Vec<i16> = looping.take(56).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(samples[14..28], samples[28..42]);
+/// assert_eq!(samples[14..28], samples[42..56]);
+/// ```
+pub struct LoopingDecoder<R: Read + Seek, C: Channels> {
+    /// The decoder for the audio stream
+    decoder: Decoder<R, C>,
+    /// The position of the loop start in the output
+    loop_start: u64,
+    /// The position of the loop end in the output
+    loop_end: u64,
+    /// The amount of samples per channel that are crossfaded
+    crossfade: u64,
+    /// The first samples after the loop start, used for the crossfade
+    start_samples: Vec<i16>,
+    /// The state of the decoder where decoding continues after the loop end
+    snapshot: Option<DecoderState<C>>,
+}
+
+impl<R: Read + Seek, C: Channels> LoopingDecoder<R, C> {
+    /// Repeat the samples from `loop_start` up to (but not including) `loop_end`.
+    ///
+    /// The loop points are in samples of *one* channel, relative to the current position
+    /// of the decoder.
+    ///
+    /// # Panics
+    /// Panics if `loop_start` is not smaller than `loop_end`.
+    pub fn new(decoder: Decoder<R, C>, loop_start: u32, loop_end: u32) -> Self {
+        assert!(
+            loop_start < loop_end,
+            "The loop start must be before the loop end"
+        );
+        let position = Decoder::position(&decoder);
+        let channels = Self::channels();
+        Self {
+            decoder,
+            loop_start: position + u64::from(loop_start) * channels,
+            loop_end: position + u64::from(loop_end) * channels,
+            crossfade: 0,
+            start_samples: Vec::new(),
+            snapshot: None,
+        }
+    }
+
+    /// Linearly crossfade the last `samples` before the loop end with the first `samples`
+    /// after the loop start.
+    ///
+    /// `samples` is the amount of samples in *one* channel, and is limited to the length of
+    /// the loop. By default there is no crossfade and the loop is bit-exact.
+    ///
+    /// This changes the output! It is intended for audio where the loop points weren't
+    /// chosen for seamless ADPCM looping, and a click can be heard at the seam. After the
+    /// first loop, decoding continues `samples` after the loop start, as those are already
+    /// faded in.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp, LoopingDecoder};
+    /// use std::io::Cursor;
+    ///
+    /// let frames = [0x00, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// let decoder = Decoder::mono(Cursor::new(frames), Dsp::default(), 2);
+    /// let looping = LoopingDecoder::new(decoder, 0, 28).with_crossfade(4);
+    /// let samples: Vec<i16> = looping.take(56).collect::<Result<_, _>>().unwrap();
+    /// // The silence at the end fades into the first frame
+    /// assert_eq!(samples[24..28], [1, 2, 4, 5]);
+    /// // And the loop continues after the faded in samples
+    /// assert_eq!(samples[28..52], samples[4..28]);
+    /// ```
+    #[must_use]
+    pub fn with_crossfade(mut self, samples: usize) -> Self {
+        let length = (self.loop_end - self.loop_start) / Self::channels();
+        self.crossfade = u64::try_from(samples).map_or(length, |samples| samples.min(length));
+        self
+    }
+
+    /// The position where decoding continues after the loop end.
+    fn resume_position(&self) -> u64 {
+        self.loop_start + self.crossfade * Self::channels()
+    }
+
+    /// The amount of channels in the output.
+    fn channels() -> u64 {
+        u64::try_from(C::CHANNEL_COUNT).unwrap_or_else(|_| unreachable!())
+    }
+
+    /// Blend a sample in the crossfade region before the loop end with the samples from
+    /// after the loop start.
+    fn crossfade(&self, position: u64, sample: i16) -> i16 {
+        let channels = Self::channels();
+        let fade_start = self.loop_end - self.crossfade * channels;
+        let Some(offset) = position.checked_sub(fade_start) else {
+            return sample;
+        };
+        let index = usize::try_from(offset).unwrap_or_else(|_| unreachable!());
+        let Some(&start) = self.start_samples.get(index) else {
+            return sample;
+        };
+        // Weights of the loop start and loop end sample
+        let fade_in = i64::try_from(offset / channels + 1).unwrap_or_else(|_| unreachable!());
+        let total = i64::try_from(self.crossfade + 1).unwrap_or_else(|_| unreachable!());
+        let fade_out = total - fade_in;
+        let blended = (i64::from(sample) * fade_out + i64::from(start) * fade_in) / total;
+        clamp(i32::try_from(blended).unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<R: Read + Seek, C: Channels> Iterator for LoopingDecoder<R, C> {
+    type Item = Result<i16, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut position = Decoder::position(&self.decoder);
+        if position == self.loop_end {
+            if let Some(snapshot) = self.snapshot.clone() {
+                if let Err(e) = self.decoder.restore(snapshot) {
+                    return Some(Err(e));
+                }
+                position = Decoder::position(&self.decoder);
+            }
+        }
+        if position == self.resume_position() && self.snapshot.is_none() {
+            match self.decoder.snapshot() {
+                Ok(snapshot) => self.snapshot = Some(snapshot),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let sample = match self.decoder.next()? {
+            Ok(sample) => sample,
+            Err(e) => return Some(Err(e)),
+        };
+        if position >= self.loop_start && position < self.resume_position() {
+            self.start_samples.push(sample);
+        }
+        if position < self.loop_end && self.crossfade != 0 {
+            return Some(Ok(self.crossfade(position, sample)));
+        }
+        Some(Ok(sample))
+    }
+}
+
+impl<R: Read + Seek, C: Channels> FusedIterator for LoopingDecoder<R, C> {}
@@ -0,0 +1,172 @@
+//! A decoder for a fixed amount of channels that each have their own reader.
+use super::PcmReader;
+use crate::{Dsp, FRAME_SIZE};
+use std::io::Read;
+use std::iter::FusedIterator;
+
+/// Decoder for `N` channels where each channel has their own reader.
+///
+/// One frame of every channel is decoded at a time, and the samples are interleaved per
+/// sample in the order of the readers. Everything is stored inline, so no allocations
+/// are needed.
+///
+/// Like [`Decoder`](crate::Decoder), an error of a reader is returned once, after which
+/// the iterator returns `None`. The readers are not read after an error, as the frames of
+/// the channels would no longer line up.
+///
+/// ```
+/// use gc_adpcm::{Dsp, PlanarDecoder};
+/// use std::io::Cursor;
+///
+/// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+/// let readers = [(); 6].map(|()| Cursor::new(frame));
+/// let decoder = PlanarDecoder::new(readers, [Dsp::default(); 6], 1);
+/// assert_eq!(decoder.count(), 6 * 14);
+/// ```
+pub struct PlanarDecoder<R: Read, const N: usize> {
+    /// The readers for the audio streams of every channel
+    readers: [R; N],
+    /// The DSP states of every channel
+    states: [Dsp; N],
+    /// The amount of frames that still need to be decoded per channel
+    frames_remaing: u32,
+    /// The decoded frame of every channel
+    frames: [[i16; 14]; N],
+    /// The index of the next sample in the interleaved output of `frames`
+    position: usize,
+    /// Reading failed, no more samples will be returned
+    failed: bool,
+}
+
+impl<R: Read, const N: usize> PlanarDecoder<R, N> {
+    /// Decode `N` audio streams.
+    ///
+    /// `channel_frames` is the amount of frames in *one* channel.
+    pub const fn new(readers: [R; N], states: [Dsp; N], channel_frames: u32) -> Self {
+        const { assert!(N > 0, "There needs to be at least one channel") };
+        Self {
+            readers,
+            states,
+            frames_remaing: channel_frames,
+            frames: [[0; 14]; N],
+            position: 14 * N,
+            failed: false,
+        }
+    }
+
+    /// Decode the next frame of every channel if all samples of the current frames are consumed.
+    fn fill_buffer(&mut self) -> Result<(), std::io::Error> {
+        if self.position == 14 * N && self.frames_remaing != 0 {
+            let mut frames = [[0; FRAME_SIZE]; N];
+            for (reader, frame) in self.readers.iter_mut().zip(frames.iter_mut()) {
+                reader.read_exact(frame)?;
+            }
+            for ((state, frame), out) in self.states.iter_mut().zip(frames).zip(&mut self.frames) {
+                state.decode_frame_into(frame, out);
+            }
+            self.frames_remaing -= 1;
+            self.position = 0;
+        }
+        Ok(())
+    }
+
+    /// Get the next sample without consuming it.
+    ///
+    /// If needed, the next frame of every channel is decoded. Calling `peek` multiple
+    /// times returns the same sample, which is also the sample returned by the next call
+    /// to `next`.
+    pub fn peek(&mut self) -> Option<Result<i16, std::io::Error>> {
+        if self.failed {
+            return None;
+        }
+        if let Err(e) = self.fill_buffer() {
+            self.failed = true;
+            return Some(Err(e));
+        }
+        if self.position == 14 * N {
+            return None;
+        }
+        Some(Ok(self.frames[self.position % N][self.position / N]))
+    }
+
+    /// Convert the decoder into a reader that outputs the samples as little-endian bytes.
+    ///
+    /// The samples are interleaved per sample in the order of the readers, which is also
+    /// the order used by WAV files. Errors of the readers are returned by the reader.
+    ///
+    /// ```
+    /// use gc_adpcm::{Dsp, PlanarDecoder};
+    /// use std::io::{Cursor, Read};
+    ///
+    /// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let readers = [(); 6].map(|()| Cursor::new(frame));
+    /// let decoder = PlanarDecoder::new(readers, [Dsp::default(); 6], 1);
+    /// let mut bytes = Vec::new();
+    /// decoder.into_pcm_reader().read_to_end(&mut bytes)?;
+    /// assert_eq!(bytes.len(), 6 * 14 * 2);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn into_pcm_reader(self) -> impl Read {
+        PcmReader::new(self)
+    }
+
+    /// Decode samples into `buf`.
+    ///
+    /// Returns the amount of samples written, which is less than the length of `buf` only
+    /// if the end of the stream is reached.
+    ///
+    /// # Errors
+    /// Returns an error if a reader fails. `buf` may already contain some of the decoded samples.
+    pub fn read_samples(&mut self, buf: &mut [i16]) -> Result<usize, std::io::Error> {
+        let mut written = 0;
+        for out in buf {
+            match self.next() {
+                Some(Ok(sample)) => *out = sample,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            }
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+impl<R: Read, const N: usize> Iterator for PlanarDecoder<R, N> {
+    type Item = Result<i16, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.peek();
+        if let Some(Ok(_)) = sample {
+            self.position += 1;
+        }
+        sample
+    }
+}
+
+impl<R: Read, const N: usize> FusedIterator for PlanarDecoder<R, N> {}
+
+#[cfg(test)]
+mod tests {
+    //! Tests for [`PlanarDecoder`].
+    use super::*;
+    use crate::test_util::{frames, Broken};
+    use std::io::Cursor;
+
+    /// After a reader fails, the error is returned once and no reader is read again.
+    #[test]
+    fn read_error_ends_decoder() {
+        let first = Cursor::new(frames(3)).chain(Broken);
+        let second = Cursor::new(frames(1)).chain(Broken);
+        let mut decoder = PlanarDecoder::new([first, second], [Dsp::default(); 2], 3);
+        let samples = decoder.by_ref().take_while(Result::is_ok).count();
+        assert_eq!(samples, 2 * 14, "Wrong amount of samples before the error");
+        assert!(decoder.peek().is_none(), "Peek continued after an error");
+        assert!(decoder.next().is_none(), "Decoder continued after an error");
+        let [first, _] = &decoder.readers;
+        assert_eq!(
+            first.get_ref().0.position(),
+            16,
+            "First reader was read again"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! A decoder where the compressed data is pushed into the decoder.
+use super::FrameBuffer;
+use crate::{Dsp, FRAME_SIZE};
+use std::collections::VecDeque;
+
+/// Decoder where the compressed data is pushed with [`PushDecoder::feed`] instead of read
+/// from a reader.
+///
+/// This is useful when the data arrives in chunks of arbitrary size, for example from a
+/// non-blocking socket. Data is only decoded when a full frame (or a frame of both
+/// channels for stereo) is available, the rest is kept until more data is fed.
+///
+/// The iterator returns `None` when more data is needed, and continues after more data is
+/// fed. It is therefore not fused.
+///
+/// ```
+/// use gc_adpcm::{Dsp, PushDecoder};
+///
+/// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+/// let mut decoder = PushDecoder::mono(Dsp::default());
+/// decoder.feed(&frame[..5]);
+/// assert_eq!(decoder.next(), None);
+/// decoder.feed(&frame[5..]);
+/// assert_eq!(decoder.by_ref().count(), 14);
+/// ```
+pub struct PushDecoder {
+    /// The DSP state of the left/mono channel
+    left_state: Dsp,
+    /// The DSP state of the right channel, only available for stereo
+    right_state: Option<Dsp>,
+    /// Compressed data that is not yet decoded
+    pending: VecDeque<u8>,
+    /// Buffer for the decoded frame(s)
+    buffer: FrameBuffer,
+}
+
+impl PushDecoder {
+    /// Decode a mono audio stream.
+    #[must_use]
+    pub const fn mono(state: Dsp) -> Self {
+        Self {
+            left_state: state,
+            right_state: None,
+            pending: VecDeque::new(),
+            buffer: FrameBuffer::new(),
+        }
+    }
+
+    /// Decode a stereo audio stream interleaved per frame.
+    ///
+    /// The samples are interleaved per sample, starting with the left channel.
+    #[must_use]
+    pub const fn interleaved_stereo(left_state: Dsp, right_state: Dsp) -> Self {
+        Self {
+            left_state,
+            right_state: Some(right_state),
+            pending: VecDeque::new(),
+            buffer: FrameBuffer::new(),
+        }
+    }
+
+    /// Add compressed data to the decoder.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend(bytes);
+    }
+
+    /// The amount of compressed bytes that are fed but not yet decoded.
+    #[must_use]
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Decode samples into `buf`.
+    ///
+    /// Returns the amount of samples written, which is less than the length of `buf` if
+    /// more data is needed.
+    pub fn read_samples(&mut self, buf: &mut [i16]) -> usize {
+        let mut written = 0;
+        for (out, sample) in buf.iter_mut().zip(self.by_ref()) {
+            *out = sample;
+            written += 1;
+        }
+        written
+    }
+
+    /// Decode the next frame(s) into the buffer if it is empty and enough data is available.
+    fn fill_buffer(&mut self) {
+        if !self.buffer.is_empty() {
+            return;
+        }
+        match self.right_state.as_mut() {
+            None if self.pending.len() >= FRAME_SIZE => {
+                let frame = take_frame(&mut self.pending);
+                self.buffer.fill(&self.left_state.decode_frame(frame));
+            }
+            Some(right_state) if self.pending.len() >= FRAME_SIZE * 2 => {
+                let left_frame = take_frame(&mut self.pending);
+                let right_frame = take_frame(&mut self.pending);
+                let left = self.left_state.decode_frame(left_frame);
+                let right = right_state.decode_frame(right_frame);
+                self.buffer.fill_interleaved(&left, &right);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Iterator for PushDecoder {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.fill_buffer();
+        self.buffer.pop_front()
+    }
+}
+
+/// Remove the first frame from `pending`, which must contain at least one frame.
+fn take_frame(pending: &mut VecDeque<u8>) -> [u8; FRAME_SIZE] {
+    let mut frame = [0; FRAME_SIZE];
+    for (byte, pending) in frame.iter_mut().zip(pending.drain(..FRAME_SIZE)) {
+        *byte = pending;
+    }
+    frame
+}
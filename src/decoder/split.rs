@@ -0,0 +1,159 @@
+//! Split the output of a stereo decoder into an iterator per channel.
+use super::{next_pair, Channel, Channels, Decoder};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::iter::FusedIterator;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The decoder and the samples that one channel already decoded for the other channel.
+struct Shared<R: Read, C: Channels> {
+    /// The decoder for both channels
+    decoder: Decoder<R, C>,
+    /// Samples of the left channel that are not returned yet
+    left: VecDeque<Result<i16, SplitError>>,
+    /// Samples of the right channel that are not returned yet
+    right: VecDeque<Result<i16, SplitError>>,
+}
+
+/// Error returned by both channels of a [`SplitChannel`].
+///
+/// The decoder fails only once, so both channels share the same error.
+#[derive(Debug, Clone)]
+pub struct SplitError(Arc<std::io::Error>);
+
+impl SplitError {
+    /// The kind of the error of the reader.
+    #[must_use]
+    pub fn kind(&self) -> std::io::ErrorKind {
+        self.0.kind()
+    }
+
+    /// The error of the reader.
+    #[must_use]
+    pub fn error(&self) -> &std::io::Error {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for SplitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SplitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+impl From<SplitError> for std::io::Error {
+    fn from(e: SplitError) -> Self {
+        Self::new(e.kind(), e)
+    }
+}
+
+/// Iterator over the samples of one channel of a stereo decoder, created by
+/// [`Decoder::split`].
+///
+/// Both channels share the decoder. If one channel is consumed faster than the other,
+/// the samples of the other channel are buffered until they are consumed. An error is
+/// returned by both channels as a [`SplitError`].
+pub struct SplitChannel<R: Read, C: Channels> {
+    /// The state that is shared with the other channel
+    shared: Rc<RefCell<Shared<R, C>>>,
+    /// The channel that is returned by this iterator
+    channel: Channel,
+}
+
+impl<R: Read, C: Channels> SplitChannel<R, C> {
+    /// Split `decoder` into an iterator for the left and the right channel.
+    pub(super) fn new(decoder: Decoder<R, C>) -> (Self, Self) {
+        let shared = Rc::new(RefCell::new(Shared {
+            decoder,
+            left: VecDeque::new(),
+            right: VecDeque::new(),
+        }));
+        let left = Self {
+            shared: Rc::clone(&shared),
+            channel: Channel::Left,
+        };
+        let right = Self {
+            shared,
+            channel: Channel::Right,
+        };
+        (left, right)
+    }
+
+    /// The channel that is returned by this iterator.
+    #[must_use]
+    pub const fn channel(&self) -> Channel {
+        self.channel
+    }
+}
+
+impl<R: Read, C: Channels> Iterator for SplitChannel<R, C> {
+    type Item = Result<i16, SplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut shared = self.shared.borrow_mut();
+        let shared = &mut *shared;
+        let (own, other) = match self.channel {
+            Channel::Left => (&mut shared.left, &mut shared.right),
+            Channel::Right => (&mut shared.right, &mut shared.left),
+        };
+        if let Some(sample) = own.pop_front() {
+            return Some(sample);
+        }
+        match next_pair(&mut shared.decoder)? {
+            Ok((left, right)) => {
+                let (sample, other_sample) = match self.channel {
+                    Channel::Left => (left, right),
+                    Channel::Right => (right, left),
+                };
+                other.push_back(Ok(other_sample));
+                Some(Ok(sample))
+            }
+            Err(e) => {
+                let e = SplitError(Arc::new(e));
+                other.push_back(Err(e.clone()));
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<R: Read, C: Channels> FusedIterator for SplitChannel<R, C> {}
+
+#[cfg(test)]
+mod tests {
+    //! Tests for [`SplitChannel`].
+    use crate::test_util::{frames, Broken};
+    use crate::{Decoder, Dsp};
+    use std::io::{Cursor, Read};
+
+    /// Both channels return the same error of the reader.
+    #[test]
+    fn read_error_shared() {
+        let reader = Cursor::new(frames(2)).chain(Broken);
+        let decoder = Decoder::interleaved_stereo(reader, Dsp::default(), Dsp::default(), 2);
+        let (left, right) = decoder.split();
+        let left_error = left.skip(14).find_map(Result::err).expect("No left error");
+        let right_error = right
+            .skip(14)
+            .find_map(Result::err)
+            .expect("No right error");
+        assert!(
+            std::ptr::eq(left_error.error(), right_error.error()),
+            "The channels returned different errors"
+        );
+        assert_eq!(right_error.error().to_string(), "broken", "Wrong error");
+        let error = std::io::Error::from(left_error);
+        assert!(
+            error.get_ref().is_some(),
+            "Source was lost in the conversion"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Statistics about the frames decoded by a [`Decoder`](crate::Decoder).
+
+/// Statistics about the decoded frames, collected by [`Decoder::with_stats`](crate::Decoder::with_stats).
+///
+/// For stereo audio streams the statistics of both channels are combined. The samples are
+/// counted before the gain is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// The amount of frames that used a scale, indexed by the lower nibble of the header
+    pub scales: [u64; 16],
+    /// The amount of frames that used a coefficient pair, indexed by the coefficient index
+    pub coefficient_indices: [u64; 8],
+    /// The smallest decoded sample, `None` if nothing is decoded
+    pub min_sample: Option<i16>,
+    /// The largest decoded sample, `None` if nothing is decoded
+    pub max_sample: Option<i16>,
+    /// The amount of samples that are at the limits of the clamp range, which is the range
+    /// of an [`i16`] unless [`Dsp::with_clamp`](crate::Dsp::with_clamp) is used
+    ///
+    /// These samples were (almost always) clamped. Many clamped samples indicate wrong
+    /// coefficients or corrupt data.
+    pub clamped_samples: u64,
+}
+
+impl Default for DecodeStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecodeStats {
+    /// Create statistics where nothing is decoded yet.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            scales: [0; 16],
+            coefficient_indices: [0; 8],
+            min_sample: None,
+            max_sample: None,
+            clamped_samples: 0,
+        }
+    }
+
+    /// The amount of frames that are counted.
+    #[must_use]
+    pub fn frames(&self) -> u64 {
+        self.scales.iter().sum()
+    }
+
+    /// Add a frame with header byte `header` that decoded to `samples`, clamped to `(min, max)`.
+    pub(crate) fn record(&mut self, header: u8, samples: &[i16; 14], (min, max): (i16, i16)) {
+        self.scales[usize::from(header & 0xF)] += 1;
+        self.coefficient_indices[usize::from((header >> 4) & 0x7)] += 1;
+        for &sample in samples {
+            self.min_sample = Some(self.min_sample.map_or(sample, |min| min.min(sample)));
+            self.max_sample = Some(self.max_sample.map_or(sample, |max| max.max(sample)));
+            if sample == min || sample == max {
+                self.clamped_samples += 1;
+            }
+        }
+    }
+}
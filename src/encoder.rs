@@ -0,0 +1,178 @@
+//! Encode PCM samples to ADPCM frames.
+use crate::{Dsp, FRAME_SIZE};
+
+#[cfg(feature = "std")]
+mod writer;
+
+#[cfg(feature = "std")]
+pub use writer::*;
+
+impl Dsp {
+    /// Encode 14 samples to a single frame of ADPCM data.
+    ///
+    /// Every combination of coefficient pair and scale is tried, and the one with the
+    /// smallest error is used. The coefficients are not changed, so they need to be
+    /// suitable for the audio. The history is updated exactly like when decoding the
+    /// frame, so the frames need to be encoded sequentially.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [0, 0, 2048, 0, 4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// // A ramp is predicted perfectly by the coefficients `4096` and `-2048`
+    /// let samples = [0, 96, 192, 288, 384, 480, 576, 672, 768, 864, 960, 1056, 1152, 1248];
+    /// let frame = Dsp::from_coefficients(coefficients).encode_frame(samples);
+    /// let decoded = Dsp::from_coefficients(coefficients).decode_frame(frame);
+    /// assert_eq!(decoded, samples);
+    /// ```
+    ///
+    /// Decoded frames are encoded exactly, so encoding them again gives the same samples.
+    pub fn encode_frame(&mut self, samples: [i16; 14]) -> [u8; FRAME_SIZE] {
+        let mut best = ([0; FRAME_SIZE], i64::MAX);
+        for coef_index in 0..8u8 {
+            for scale in 0..16u8 {
+                let header = (coef_index << 4) | scale;
+                let candidate = self.encode_frame_with_header(header, &samples);
+                if candidate.1 < best.1 {
+                    best = candidate;
+                }
+            }
+        }
+        let frame = best.0;
+        // Update the history exactly like the decoder does
+        self.decode_frame(frame);
+        frame
+    }
+
+    /// Encode all samples to frames.
+    ///
+    /// The last frame is padded with silence if the amount of samples is not a multiple of 14.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [0, 0, 2048, 0, 4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let samples: Vec<i16> = (0..20).map(|i| i * 96).collect();
+    /// let frames = Dsp::from_coefficients(coefficients).encode_all(&samples);
+    /// assert_eq!(frames.len(), 2);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encode_all(&mut self, samples: &[i16]) -> Vec<[u8; FRAME_SIZE]> {
+        samples
+            .chunks(14)
+            .map(|chunk| {
+                let mut frame = [0; 14];
+                frame[..chunk.len()].copy_from_slice(chunk);
+                self.encode_frame(frame)
+            })
+            .collect()
+    }
+
+    /// Encode the samples using the coefficients and scale of `header`.
+    ///
+    /// Returns the frame and the sum of the squared errors. The history is not changed.
+    fn encode_frame_with_header(&self, header: u8, samples: &[i16; 14]) -> ([u8; FRAME_SIZE], i64) {
+        let mut dsp = *self;
+        let (scale, coef1, coef2) = dsp.parameters(header);
+        let mut frame = [header, 0, 0, 0, 0, 0, 0, 0];
+        let mut error = 0;
+        for (i, &sample) in samples.iter().enumerate() {
+            let prediction = coef1 * i64::from(dsp.hist1) + coef2 * i64::from(dsp.hist2);
+            // Find the nibble that is closest to the sample. The decoder adds 1024 before
+            // rounding down, so halves are rounded down here to invert that exactly.
+            let numerator = (i64::from(sample) << 11) - prediction;
+            let denominator = scale << 11;
+            let nibble = (-(denominator - 2 * numerator).div_euclid(2 * denominator)).clamp(-8, 7);
+            let nibble = i32::try_from(nibble).unwrap_or_else(|_| unreachable!());
+            let decoded = dsp.decode_unclamped(scale, coef1, coef2, 1024, nibble);
+            let decoded = dsp.clamp_sample(decoded);
+            error += (i64::from(decoded) - i64::from(sample)).pow(2);
+
+            let nibble = u8::try_from(nibble & 0xF).unwrap_or_else(|_| unreachable!());
+            if i % 2 == 0 {
+                frame[1 + i / 2] |= nibble << 4;
+            } else {
+                frame[1 + i / 2] |= nibble;
+            }
+        }
+        (frame, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Round trip tests for the encoder.
+    use crate::Dsp;
+
+    /// Decoded frames are encoded exactly, and the history matches the decoder.
+    #[test]
+    fn encode_decoded_frames() {
+        let mut seed = 0x1234_5678_u32;
+        let mut random = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed.to_le_bytes()[0]
+        };
+
+        let mut dsp =
+            Dsp::from_coefficients([1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        for _ in 0..1000 {
+            // Coefficient pair 0 with a scale of 1, 2, or 4
+            let frame = [
+                random() % 3,
+                random(),
+                random(),
+                random(),
+                random(),
+                random(),
+                random(),
+                random(),
+            ];
+            let mut encoder = dsp;
+            let samples = dsp.decode_frame(frame);
+            let mut decoder = encoder;
+            let frame = encoder.encode_frame(samples);
+            assert_eq!(decoder.decode_frame(frame), samples, "Frame is not exact");
+            assert_eq!(encoder, dsp, "History differs from the decoder");
+        }
+    }
+
+    /// A sine is encoded with a small error.
+    #[cfg(feature = "std")]
+    #[test]
+    fn encode_all_sine() -> Result<(), std::io::Error> {
+        // A 440 Hz sine with a sample rate of 32 kHz, which is predicted well by the
+        // coefficients `2 * cos(2π * 440 / 32000) * 2048` and `-2048`
+        let coefficients = [
+            0, 0, 2048, 0, 4080, -2048, 4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let samples: Vec<i16> = (0..1000)
+            .map(|i| (f64::from(i) * std::f64::consts::TAU * 440.0 / 32000.0).sin() * 10000.0)
+            .map(|sample| {
+                #[allow(
+                    clippy::as_conversions,
+                    clippy::cast_possible_truncation,
+                    reason = "The sine is between -10000 and 10000."
+                )]
+                let sample = sample.round() as i16;
+                sample
+            })
+            .collect();
+        let frames = Dsp::from_coefficients(coefficients).encode_all(&samples);
+        assert_eq!(frames.len(), 72, "Wrong amount of frames");
+
+        let data = frames.as_flattened();
+        let decoded =
+            crate::Decoder::mono_samples(data, Dsp::from_coefficients(coefficients), 1000)
+                .collect::<Result<Vec<_>, _>>()?;
+        let squared_error: f64 = samples
+            .iter()
+            .zip(&decoded)
+            .map(|(&sample, &decoded)| (f64::from(sample) - f64::from(decoded)).powi(2))
+            .sum();
+        let rms = (squared_error / 1000.0).sqrt();
+        assert!(rms < 50.0, "RMS error is {rms}");
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! An encoder that takes `i16` samples and writes the frames to a `std::io::Write`.
+use crate::{Channels, Dsp, Mono, Stereo, StereoInterleaved};
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Encoder that writes the encoded frames to one or two writers.
+///
+/// This is the counterpart of [`Decoder`](crate::Decoder), and supports the same channel
+/// layouts. For stereo the samples need to be interleaved per sample, starting with the
+/// left channel. The samples are buffered until a full frame of every channel is available.
+///
+/// [`Encoder::finish`] needs to be called to write the last partial frame, which is padded
+/// with silence.
+///
+/// If writing fails, it is unknown how much of the frame(s) was written. Every write after
+/// that returns an error, as the output could no longer be decoded correctly.
+///
+/// ```
+/// use gc_adpcm::{Decoder, Dsp, Encoder};
+/// use std::io::Cursor;
+///
+/// let coefficients = [0, 0, 2048, 0, 4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+/// let state = Dsp::from_coefficients(coefficients);
+/// let samples: Vec<i16> = (0..30).map(|i| i * 96).collect();
+///
+/// let mut encoder = Encoder::mono(Vec::new(), state);
+/// encoder.write_samples(&samples)?;
+/// let data = encoder.finish()?;
+/// assert_eq!(data.len(), 3 * 8);
+///
+/// let decoded = Decoder::mono_samples(Cursor::new(data), state, 30).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(decoded.len(), 30);
+/// // A ramp is predicted perfectly, but the last frame is padded with silence
+/// assert_eq!(decoded[..28], samples[..28]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Encoder<W: Write, C: Channels> {
+    /// The writer for the left/mono/interleaved audio stream
+    left_writer: W,
+    /// The writer for the right channel audio stream, only available on [`Stereo`]
+    right_writer: Option<W>,
+    /// The DSP state of the left/mono channel
+    left_state: Dsp,
+    /// The DSP state of the right channel, not available when channel is [`Mono`]
+    right_state: Option<Dsp>,
+    /// Samples that are not encoded yet, interleaved for stereo
+    buffer: Vec<i16>,
+    /// If writing failed, after which nothing is written anymore
+    failed: bool,
+    /// Fake field for the [`Channels`] typestate
+    _phantom_data: PhantomData<C>,
+}
+
+impl<W: Write> Encoder<W, Mono> {
+    /// Encode a mono audio stream.
+    pub fn mono(writer: W, state: Dsp) -> Self {
+        Self {
+            left_writer: writer,
+            right_writer: None,
+            left_state: state,
+            right_state: None,
+            buffer: Vec::with_capacity(Self::FRAME_SAMPLES),
+            failed: false,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Write the last partial frame and return the writer.
+    ///
+    /// # Errors
+    /// Returns an error if writing or flushing fails, or if writing failed before.
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        self.write_partial_frame()?;
+        self.left_writer.flush()?;
+        Ok(self.left_writer)
+    }
+}
+
+impl<W: Write> Encoder<W, Stereo> {
+    /// Encode a stereo audio stream where each channel has their own buffer.
+    pub fn stereo(left_writer: W, left_state: Dsp, right_writer: W, right_state: Dsp) -> Self {
+        Self {
+            left_writer,
+            right_writer: Some(right_writer),
+            left_state,
+            right_state: Some(right_state),
+            buffer: Vec::with_capacity(Self::FRAME_SAMPLES),
+            failed: false,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Write the last partial frame of both channels and return the writers.
+    ///
+    /// # Errors
+    /// Returns an error if writing or flushing fails, or if writing failed before.
+    pub fn finish(mut self) -> Result<(W, W), std::io::Error> {
+        self.write_partial_frame()?;
+        let mut right_writer = self.right_writer.unwrap_or_else(|| unreachable!());
+        self.left_writer.flush()?;
+        right_writer.flush()?;
+        Ok((self.left_writer, right_writer))
+    }
+}
+
+impl<W: Write> Encoder<W, StereoInterleaved> {
+    /// Encode a stereo audio stream interleaved per frame.
+    ///
+    /// ```
+    /// use gc_adpcm::{Decoder, Dsp, Encoder};
+    /// use std::io::Cursor;
+    ///
+    /// let state = Dsp::from_coefficients([4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    /// let samples: Vec<i16> = (0..28).flat_map(|i| [i * 32, i * -64]).collect();
+    /// let mut encoder = Encoder::interleaved_stereo(Vec::new(), state, state);
+    /// encoder.write_samples(&samples)?;
+    /// let data = encoder.finish()?;
+    /// let decoder = Decoder::interleaved_stereo(Cursor::new(data), state, state, 2);
+    /// assert_eq!(decoder.collect::<Result<Vec<_>, _>>()?, samples);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn interleaved_stereo(writer: W, left_state: Dsp, right_state: Dsp) -> Self {
+        Self {
+            left_writer: writer,
+            right_writer: None,
+            left_state,
+            right_state: Some(right_state),
+            buffer: Vec::with_capacity(Self::FRAME_SAMPLES),
+            failed: false,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Write the last partial frame of both channels and return the writer.
+    ///
+    /// # Errors
+    /// Returns an error if writing or flushing fails, or if writing failed before.
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        self.write_partial_frame()?;
+        self.left_writer.flush()?;
+        Ok(self.left_writer)
+    }
+}
+
+impl<W: Write, C: Channels> Encoder<W, C> {
+    /// The amount of samples in one frame of every channel.
+    const FRAME_SAMPLES: usize = 14 * C::CHANNEL_COUNT;
+
+    /// Add a sample, and write the frame(s) if they are complete.
+    ///
+    /// # Errors
+    /// Returns an error if writing fails, or if writing failed before.
+    pub fn write_sample(&mut self, sample: i16) -> Result<(), std::io::Error> {
+        self.check_failed()?;
+        self.buffer.push(sample);
+        if self.buffer.len() == Self::FRAME_SAMPLES {
+            self.write_frames()?;
+        }
+        Ok(())
+    }
+
+    /// Add all samples, and write the frames that are complete.
+    ///
+    /// # Errors
+    /// Returns an error if writing fails, or if writing failed before. Some of the samples
+    /// may already be written.
+    pub fn write_samples(&mut self, samples: &[i16]) -> Result<(), std::io::Error> {
+        for &sample in samples {
+            self.write_sample(sample)?;
+        }
+        Ok(())
+    }
+
+    /// Return an error if writing failed before.
+    fn check_failed(&self) -> Result<(), std::io::Error> {
+        if self.failed {
+            Err(std::io::Error::other("writing a previous frame failed"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Pad the buffer with silence and write the frame(s), if there are buffered samples.
+    fn write_partial_frame(&mut self) -> Result<(), std::io::Error> {
+        self.check_failed()?;
+        if !self.buffer.is_empty() {
+            self.buffer.resize(Self::FRAME_SAMPLES, 0);
+            self.write_frames()?;
+        }
+        Ok(())
+    }
+
+    /// Encode the full buffer and write the frame(s).
+    ///
+    /// If writing fails, the encoder is marked as failed.
+    fn write_frames(&mut self) -> Result<(), std::io::Error> {
+        let result = self.encode_and_write();
+        self.failed = result.is_err();
+        self.buffer.clear();
+        result
+    }
+
+    /// Encode the full buffer and write the frame(s), without clearing the buffer.
+    fn encode_and_write(&mut self) -> Result<(), std::io::Error> {
+        if C::CHANNEL_COUNT == 2 {
+            let right_state = self.right_state.as_mut().unwrap_or_else(|| unreachable!());
+            let mut left = [0; 14];
+            let mut right = [0; 14];
+            for ((left, right), samples) in left
+                .iter_mut()
+                .zip(right.iter_mut())
+                .zip(self.buffer.chunks_exact(2))
+            {
+                *left = samples[0];
+                *right = samples[1];
+            }
+            let left_frame = self.left_state.encode_frame(left);
+            let right_frame = right_state.encode_frame(right);
+            self.left_writer.write_all(&left_frame)?;
+            if C::FRAMES_PER_STEP == 2 {
+                self.left_writer.write_all(&right_frame)?;
+            } else {
+                self.right_writer
+                    .as_mut()
+                    .unwrap_or_else(|| unreachable!())
+                    .write_all(&right_frame)?;
+            }
+        } else {
+            let mut samples = [0; 14];
+            samples.copy_from_slice(&self.buffer);
+            let frame = self.left_state.encode_frame(samples);
+            self.left_writer.write_all(&frame)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests for [`Encoder`].
+    use super::*;
+
+    /// A writer that fails after `limit` bytes.
+    struct Limited {
+        /// The written bytes
+        data: Vec<u8>,
+        /// The amount of bytes that can be written
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.limit - self.data.len());
+            if len == 0 {
+                return Err(std::io::Error::other("full"));
+            }
+            self.data.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// After a failed write nothing is written anymore.
+    #[test]
+    fn write_error_poisons() {
+        let samples: Vec<i16> = (0..28).collect();
+        let writer = Limited {
+            data: Vec::new(),
+            limit: 12,
+        };
+        let mut encoder = Encoder::mono(writer, Dsp::default());
+        assert!(encoder.write_samples(&samples).is_err(), "Write succeeded");
+        assert_eq!(encoder.left_writer.data.len(), 12, "Wrong amount written");
+        encoder.left_writer.limit = 100;
+        assert!(
+            encoder.write_sample(0).is_err(),
+            "Write after an error succeeded"
+        );
+        assert!(encoder.finish().is_err(), "Finish after an error succeeded");
+    }
+}
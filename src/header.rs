@@ -0,0 +1,190 @@
+//! The header of a DSP file.
+use crate::{samples_from_nibbles, Dsp};
+
+/// The header of a DSP file, which contains the information needed to decode one channel.
+///
+/// The header is 0x60 bytes and all values are big-endian. The audio data starts directly
+/// after the header.
+///
+/// ```
+/// use gc_adpcm::{Dsp, DspHeader};
+///
+/// let header = DspHeader {
+///     sample_count: 14,
+///     nibble_count: 16,
+///     sample_rate: 32_000,
+///     looping: false,
+///     format: 0,
+///     loop_start: 2,
+///     loop_end: 15,
+///     current_address: 2,
+///     coefficients: [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+///     gain: 0,
+///     predictor_scale: 0x03,
+///     hist1: 0,
+///     hist2: 0,
+///     loop_predictor_scale: 0,
+///     loop_hist1: 0,
+///     loop_hist2: 0,
+/// };
+/// let bytes = header.to_bytes();
+/// assert_eq!(DspHeader::parse(&bytes), Ok(header));
+/// assert_eq!(header.dsp(), Dsp::from_coefficients(header.coefficients));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DspHeader {
+    /// The amount of samples
+    pub sample_count: u32,
+    /// The amount of nibbles, including the headers of the frames
+    pub nibble_count: u32,
+    /// The sample rate in Hz
+    pub sample_rate: u32,
+    /// If the audio loops
+    pub looping: bool,
+    /// The format of the audio, `0` for ADPCM
+    pub format: u16,
+    /// The start of the loop, as a nibble offset
+    pub loop_start: u32,
+    /// The end of the loop, as a nibble offset
+    pub loop_end: u32,
+    /// The initial nibble offset, usually `2`
+    pub current_address: u32,
+    /// The coefficients for decoding
+    pub coefficients: [i16; 16],
+    /// The gain, always `0`
+    pub gain: u16,
+    /// The header of the first frame
+    pub predictor_scale: u16,
+    /// The initial history
+    pub hist1: i16,
+    /// The initial history 2
+    pub hist2: i16,
+    /// The header of the frame at the loop start
+    pub loop_predictor_scale: u16,
+    /// The history at the loop start
+    pub loop_hist1: i16,
+    /// The history 2 at the loop start
+    pub loop_hist2: i16,
+}
+
+impl DspHeader {
+    /// The size of the header in bytes
+    pub const SIZE: usize = 0x60;
+
+    /// Parse and validate the header of a DSP file.
+    ///
+    /// # Errors
+    /// Returns an error if the header is not for ADPCM audio, or if the values in it are
+    /// inconsistent.
+    pub fn parse(bytes: &[u8; Self::SIZE]) -> Result<Self, HeaderError> {
+        let u32_at = |offset: usize| {
+            u32::from_be_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+        let u16_at = |offset: usize| u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        let i16_at = |offset: usize| i16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+
+        let mut coefficients = [0; 16];
+        for (i, coefficient) in coefficients.iter_mut().enumerate() {
+            *coefficient = i16_at(0x1C + i * 2);
+        }
+        let header = Self {
+            sample_count: u32_at(0x00),
+            nibble_count: u32_at(0x04),
+            sample_rate: u32_at(0x08),
+            looping: u16_at(0x0C) != 0,
+            format: u16_at(0x0E),
+            loop_start: u32_at(0x10),
+            loop_end: u32_at(0x14),
+            current_address: u32_at(0x18),
+            coefficients,
+            gain: u16_at(0x3C),
+            predictor_scale: u16_at(0x3E),
+            hist1: i16_at(0x40),
+            hist2: i16_at(0x42),
+            loop_predictor_scale: u16_at(0x44),
+            loop_hist1: i16_at(0x46),
+            loop_hist2: i16_at(0x48),
+        };
+
+        if header.format != 0 {
+            return Err(HeaderError::Format(header.format));
+        }
+        if header.sample_count > samples_from_nibbles(header.nibble_count) {
+            return Err(HeaderError::Length);
+        }
+        if header.predictor_scale > 0x7F {
+            return Err(HeaderError::PredictorScale(header.predictor_scale));
+        }
+        Ok(header)
+    }
+
+    /// Convert the header to the bytes that are stored in a DSP file.
+    ///
+    /// The unused bytes at the end are set to zero.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        let mut put = |offset: usize, value: &[u8]| {
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+        };
+        put(0x00, &self.sample_count.to_be_bytes());
+        put(0x04, &self.nibble_count.to_be_bytes());
+        put(0x08, &self.sample_rate.to_be_bytes());
+        put(0x0C, &u16::from(self.looping).to_be_bytes());
+        put(0x0E, &self.format.to_be_bytes());
+        put(0x10, &self.loop_start.to_be_bytes());
+        put(0x14, &self.loop_end.to_be_bytes());
+        put(0x18, &self.current_address.to_be_bytes());
+        for (i, coefficient) in self.coefficients.iter().enumerate() {
+            put(0x1C + i * 2, &coefficient.to_be_bytes());
+        }
+        put(0x3C, &self.gain.to_be_bytes());
+        put(0x3E, &self.predictor_scale.to_be_bytes());
+        put(0x40, &self.hist1.to_be_bytes());
+        put(0x42, &self.hist2.to_be_bytes());
+        put(0x44, &self.loop_predictor_scale.to_be_bytes());
+        put(0x46, &self.loop_hist1.to_be_bytes());
+        put(0x48, &self.loop_hist2.to_be_bytes());
+        bytes
+    }
+
+    /// The DSP state for decoding the audio from the start.
+    #[must_use]
+    pub const fn dsp(&self) -> Dsp {
+        Dsp::new(self.coefficients, self.hist1, self.hist2)
+    }
+}
+
+/// Error returned by [`DspHeader::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The format is not ADPCM
+    Format(u16),
+    /// The amount of samples does not fit in the amount of nibbles
+    Length,
+    /// The header of the first frame is not valid
+    PredictorScale(u16),
+}
+
+impl std::fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format(format) => write!(f, "the format {format} is not ADPCM"),
+            Self::Length => write!(
+                f,
+                "the amount of samples does not fit in the amount of nibbles"
+            ),
+            Self::PredictorScale(ps) => {
+                write!(f, "the header {ps:#x} of the first frame is not valid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
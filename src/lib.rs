@@ -7,84 +7,772 @@ mod decoder;
 #[doc(inline)]
 pub use decoder::*;
 
+mod slice;
+
+#[doc(inline)]
+pub use slice::*;
+
+mod header;
+
+#[doc(inline)]
+pub use header::*;
+
+mod encoder;
+
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use encoder::*;
+
+pub mod util;
+
+use util::{clamp, get_high_nibble, get_low_nibble};
+
+#[cfg(feature = "hound")]
+mod wav;
+
+#[cfg(feature = "hound")]
+#[doc(inline)]
+pub use wav::*;
+
+#[cfg(feature = "rayon")]
+mod parallel;
+
+#[cfg(feature = "rayon")]
+#[doc(inline)]
+pub use parallel::*;
+
+#[cfg(feature = "bytemuck")]
+mod cast;
+
+#[cfg(feature = "bytemuck")]
+#[doc(inline)]
+pub use cast::*;
+
+#[cfg(test)]
+mod test_util;
+
 /// State of the DSP encoder of a single channel
+///
+/// With the `serde` feature, deserializing a clamp range where the minimum is larger than
+/// the maximum returns an error, just like [`Dsp::with_clamp`] panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DspFields"))]
 pub struct Dsp {
     /// The initial history
     pub hist1: i16,
     /// The initial history 2
     pub hist2: i16,
-    /// Coefficients for the audio
+    /// Coefficients for the audio, 8 pairs that are selected by the header of a frame
     pub coefficients: [i16; 16],
+    /// The lowest value a sample is clamped to
+    clamp_min: i16,
+    /// The highest value a sample is clamped to
+    clamp_max: i16,
+    /// The order of the two samples in a byte
+    nibble_order: NibbleOrder,
+}
+
+impl Default for Dsp {
+    fn default() -> Self {
+        Self::new([0; 16], 0, 0)
+    }
+}
+
+/// The fields of [`Dsp`] before the clamp range is validated.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct DspFields {
+    /// The initial history
+    hist1: i16,
+    /// The initial history 2
+    hist2: i16,
+    /// Coefficients for the audio
+    coefficients: [i16; 16],
+    /// The lowest value a sample is clamped to
+    clamp_min: i16,
+    /// The highest value a sample is clamped to
+    clamp_max: i16,
+    /// The order of the two samples in a byte
+    nibble_order: NibbleOrder,
+}
+
+#[cfg(feature = "serde")]
+impl Default for DspFields {
+    fn default() -> Self {
+        let dsp = Dsp::default();
+        Self {
+            hist1: dsp.hist1,
+            hist2: dsp.hist2,
+            coefficients: dsp.coefficients,
+            clamp_min: dsp.clamp_min,
+            clamp_max: dsp.clamp_max,
+            nibble_order: dsp.nibble_order,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DspFields> for Dsp {
+    type Error = &'static str;
+
+    fn try_from(fields: DspFields) -> Result<Self, Self::Error> {
+        if fields.clamp_min > fields.clamp_max {
+            return Err("the minimum of the clamp range is larger than the maximum");
+        }
+        Ok(Self::new(fields.coefficients, fields.hist1, fields.hist2)
+            .with_clamp(fields.clamp_min, fields.clamp_max)
+            .with_nibble_order(fields.nibble_order))
+    }
 }
 
 impl Dsp {
+    /// Create the DSP state from the coefficients and the initial history.
+    #[must_use]
+    pub const fn new(coefficients: [i16; 16], hist1: i16, hist2: i16) -> Self {
+        Self {
+            hist1,
+            hist2,
+            coefficients,
+            clamp_min: i16::MIN,
+            clamp_max: i16::MAX,
+            nibble_order: NibbleOrder::HighFirst,
+        }
+    }
+
+    /// Clamp the decoded samples to `min..=max` instead of the full range of an [`i16`].
+    ///
+    /// The history is clamped to the same range, so the output stays consistent. This is
+    /// useful for leaving headroom for mixing, or for matching a decoder that clamps to a
+    /// smaller range.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x0C, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77];
+    /// let samples = Dsp::from_coefficients(coefficients)
+    ///     .with_clamp(-16384, 16383)
+    ///     .decode_frame(frame);
+    /// assert_eq!(samples.iter().max(), Some(&16383));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `min` is larger than `max`.
+    #[must_use]
+    pub const fn with_clamp(mut self, min: i16, max: i16) -> Self {
+        assert!(min <= max, "The minimum is larger than the maximum");
+        self.clamp_min = min;
+        self.clamp_max = max;
+        self
+    }
+
+    /// The range the decoded samples are clamped to, as `(min, max)`.
+    #[must_use]
+    pub const fn clamp_range(&self) -> (i16, i16) {
+        (self.clamp_min, self.clamp_max)
+    }
+
+    /// Decode the samples in a byte in `order` instead of high nibble first.
+    ///
+    /// Some packers store the low nibble first, which can be decoded with
+    /// [`NibbleOrder::LowFirst`].
+    ///
+    /// ```
+    /// use gc_adpcm::{Dsp, NibbleOrder};
+    ///
+    /// let frame = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let swapped = [0x00, 0x21, 0x43, 0x65, 0x87, 0xA9, 0xCB, 0xED];
+    /// let high_first = Dsp::default().decode_frame(frame);
+    /// let low_first = Dsp::default().with_nibble_order(NibbleOrder::LowFirst).decode_frame(frame);
+    /// assert_ne!(high_first, low_first);
+    /// assert_eq!(low_first, Dsp::default().decode_frame(swapped));
+    /// assert_eq!((high_first[0], low_first[0]), (1, 2));
+    /// ```
+    #[must_use]
+    pub const fn with_nibble_order(mut self, order: NibbleOrder) -> Self {
+        self.nibble_order = order;
+        self
+    }
+
+    /// The order of the two samples in a byte.
+    #[must_use]
+    pub const fn nibble_order(&self) -> NibbleOrder {
+        self.nibble_order
+    }
+
+    /// Create the DSP state from the coefficients, with the initial history set to zero.
+    #[must_use]
+    pub const fn from_coefficients(coefficients: [i16; 16]) -> Self {
+        Self::new(coefficients, 0, 0)
+    }
+
+    /// Create the DSP state from the 8 coefficient pairs, with the initial history set to zero.
+    ///
+    /// This is the same as [`Dsp::from_coefficients`] with the pairs flattened.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let pairs = [[1024, -512], [2048, -1024], [0, 0], [0, 0], [0, 0], [0, 0], [0, 0], [0, 0]];
+    /// let dsp = Dsp::from_coefficient_pairs(pairs);
+    /// assert_eq!(dsp.coefficients[..4], [1024, -512, 2048, -1024]);
+    /// assert_eq!(dsp.coefficient_pairs(), pairs);
+    /// ```
+    #[must_use]
+    pub const fn from_coefficient_pairs(pairs: [[i16; 2]; 8]) -> Self {
+        let mut coefficients = [0; 16];
+        let mut i = 0;
+        while i < 8 {
+            coefficients[i * 2] = pairs[i][0];
+            coefficients[i * 2 + 1] = pairs[i][1];
+            i += 1;
+        }
+        Self::from_coefficients(coefficients)
+    }
+
+    /// The coefficients as 8 pairs.
+    #[must_use]
+    pub const fn coefficient_pairs(&self) -> [[i16; 2]; 8] {
+        let mut pairs = [[0; 2]; 8];
+        let mut i = 0;
+        while i < 8 {
+            pairs[i] = self.coefficient_pair(i);
+            i += 1;
+        }
+        pairs
+    }
+
+    /// The coefficient pair at `index`.
+    ///
+    /// Only the lowest 3 bits of `index` are used, like the coefficient index in the header
+    /// of a frame, so this never panics.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let dsp = Dsp::from_coefficients([0, 0, 1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(dsp.coefficient_pair(1), [1024, -512]);
+    /// assert_eq!(dsp.coefficient_pair(9), [1024, -512]);
+    /// ```
+    #[must_use]
+    pub const fn coefficient_pair(&self, index: usize) -> [i16; 2] {
+        let index = index & 0x7;
+        [
+            self.coefficients[index * 2],
+            self.coefficients[index * 2 + 1],
+        ]
+    }
+
+    /// Replace the history with `hist1` and `hist2`.
+    ///
+    /// Some formats store the history at the start of every block, which should be
+    /// used instead of the history of the previous block.
+    pub fn set_history(&mut self, hist1: i16, hist2: i16) {
+        self.hist1 = hist1;
+        self.hist2 = hist2;
+    }
+
     /// Decode a single frame of ADPCM data.
     ///
     /// Note: the frames need to be parsed sequentially as the hist1 and hist2 values
     /// are updated every frame.
+    ///
+    /// There are only 8 coefficient pairs, so only the lower three bits of the
+    /// coefficient index in the header are used. This function does not panic for any
+    /// input.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2048, -1024];
+    /// let mut dsp = Dsp::from_coefficients(coefficients);
+    /// let samples = dsp.decode_frame([0xF0, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE]);
+    /// // An index of 15 is the same as an index of 7
+    /// let mut expected = Dsp::from_coefficients(coefficients);
+    /// let expected = expected.decode_frame([0x70, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE]);
+    /// assert_eq!(samples, expected);
+    /// ```
     pub fn decode_frame(&mut self, frame: [u8; FRAME_SIZE]) -> [i16; 14] {
-        let header = frame[0];
+        let mut out = [0; 14];
+        self.decode_frame_into(frame, &mut out);
+        out
+    }
 
-        let scale = 1i32 << (header & 0xF);
-        let coef_index = usize::from((header >> 4) & 0xF);
-        let coef1 = i32::from(self.coefficients[coef_index * 2]);
-        let coef2 = i32::from(self.coefficients[coef_index * 2 + 1]);
+    /// Decode a single frame of ADPCM data from a slice.
+    ///
+    /// This is the same as [`Dsp::decode_frame`], but the frame doesn't need to be copied
+    /// into an array first.
+    ///
+    /// ```
+    /// use gc_adpcm::{Dsp, FrameError};
+    ///
+    /// let data = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0x00];
+    /// let samples = Dsp::default().decode_frame_slice(&data[..8])?;
+    /// assert_eq!(samples, Dsp::default().decode_frame([0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE]));
+    /// assert_eq!(Dsp::default().decode_frame_slice(&data[..7]), Err(FrameError { len: 7 }));
+    /// assert_eq!(Dsp::default().decode_frame_slice(&data), Err(FrameError { len: 9 }));
+    /// # Ok::<(), FrameError>(())
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if `frame` is not exactly [`FRAME_SIZE`] bytes. The DSP state is not
+    /// changed.
+    pub fn decode_frame_slice(&mut self, frame: &[u8]) -> Result<[i16; 14], FrameError> {
+        let frame = frame
+            .try_into()
+            .map_err(|_| FrameError { len: frame.len() })?;
+        Ok(self.decode_frame(frame))
+    }
+
+    /// Decode a single frame of ADPCM data into `out`.
+    ///
+    /// This is the same as [`Dsp::decode_frame`], but the samples are written directly into
+    /// a buffer of the caller.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let mut samples = vec![0; 28];
+    /// let mut dsp = Dsp::default();
+    /// for (frame, out) in frames.chunks_exact(8).zip(samples.chunks_exact_mut(14)) {
+    ///     dsp.decode_frame_into(frame.try_into().unwrap(), out.try_into().unwrap());
+    /// }
+    /// let mut expected = Dsp::default();
+    /// assert_eq!(samples[..14], expected.decode_frame([0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE])[..]);
+    /// ```
+    pub fn decode_frame_into(&mut self, frame: [u8; FRAME_SIZE], out: &mut [i16; 14]) {
+        let [header, data @ ..] = frame;
+        let samples = self.decode(header, data, Rounding::Nearest);
+        for (out, sample) in out.iter_mut().zip(samples) {
+            *out = self.clamp_sample(sample);
+        }
+    }
+
+    /// Decode a single frame of ADPCM data with a different rounding than the hardware.
+    ///
+    /// [`Dsp::decode_frame`] is the same as using [`Rounding::Nearest`]. This is useful for
+    /// bit-matching the output of other decoders.
+    ///
+    /// ```
+    /// use gc_adpcm::{Dsp, Rounding};
+    ///
+    /// let coefficients = [1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x00, 0x10, 0, 0, 0, 0, 0, 0];
+    /// let nearest = Dsp::from_coefficients(coefficients).decode_frame_with(frame, Rounding::Nearest);
+    /// assert_eq!(nearest, Dsp::from_coefficients(coefficients).decode_frame(frame));
+    /// let truncate = Dsp::from_coefficients(coefficients).decode_frame_with(frame, Rounding::Truncate);
+    /// // Half of 1 is rounded up to 1 with `Nearest`, but truncated to 0 with `Truncate`
+    /// assert_eq!((nearest[1], truncate[1]), (1, 0));
+    /// ```
+    pub fn decode_frame_with(&mut self, frame: [u8; FRAME_SIZE], rounding: Rounding) -> [i16; 14] {
+        let [header, data @ ..] = frame;
+        self.decode(header, data, rounding)
+            .map(|sample| self.clamp_sample(sample))
+    }
+
+    /// Decode the data of a frame where the header is stored separately.
+    ///
+    /// Some formats store the header of every frame in a separate table, so that the
+    /// data only contains the 7 bytes with samples.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let data = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let samples = Dsp::from_coefficients(coefficients).decode_data_frame(0x03, data);
+    /// let expected = Dsp::from_coefficients(coefficients)
+    ///     .decode_frame([0x03, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE]);
+    /// assert_eq!(samples, expected);
+    /// ```
+    pub fn decode_data_frame(&mut self, header: u8, data: [u8; FRAME_SIZE - 1]) -> [i16; 14] {
+        self.decode(header, data, Rounding::Nearest)
+            .map(|sample| self.clamp_sample(sample))
+    }
+
+    /// Decode a single frame of ADPCM data without clamping the samples to [`i16`].
+    ///
+    /// This is the same as [`Dsp::decode_frame_raw`].
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x0C, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77];
+    /// let samples = Dsp::from_coefficients(coefficients).decode_frame_i32(frame);
+    /// assert_eq!(samples, Dsp::from_coefficients(coefficients).decode_frame_raw(frame));
+    /// ```
+    pub fn decode_frame_i32(&mut self, frame: [u8; FRAME_SIZE]) -> [i32; 14] {
+        self.decode_frame_raw(frame)
+    }
+
+    /// Decode a single frame of ADPCM data to the full precision intermediate.
+    ///
+    /// Every sample is exactly the value before clamping, so the output of
+    /// [`Dsp::decode_frame`] is this value clamped. This is useful for applying dither
+    /// before clamping or quantizing. The history is still updated with the clamped
+    /// samples, like the hardware does, so the next frames are not affected. It is also
+    /// useful for seeing where clamping happens when analysing an encoder.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x0C, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77];
+    /// let samples = Dsp::from_coefficients(coefficients).decode_frame_raw(frame);
+    /// assert!(samples.iter().any(|sample| *sample > i32::from(i16::MAX)));
+    /// let clamped = samples.map(|sample| sample.clamp(-32768, 32767));
+    /// let expected = Dsp::from_coefficients(coefficients).decode_frame(frame);
+    /// assert!(clamped.into_iter().eq(expected.map(i32::from)));
+    /// ```
+    ///
+    /// The samples are always in the range `-1_310_688..=1_277_952`, which fits in 22 bits.
+    pub fn decode_frame_raw(&mut self, frame: [u8; FRAME_SIZE]) -> [i32; 14] {
+        let [header, data @ ..] = frame;
+        self.decode(header, data, Rounding::Nearest)
+    }
+
+    /// Decode a single sample, and update the history.
+    ///
+    /// `scale` is the multiplier of the nibble, which is `1 << (header & 0xF)` for a frame.
+    /// It is clamped to `0..=32768`, the range that can be stored in a header.
+    /// `coef_index` selects the coefficient pair, only the lower three bits are used.
+    /// Only the lower four bits of `nibble` are used.
+    ///
+    /// This is useful for emulators that need to decode one sample at a time. Decoding
+    /// all samples of a frame with this function gives the same result as [`Dsp::decode_frame`].
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [0, 0, 1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x13, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let expected = Dsp::from_coefficients(coefficients).decode_frame(frame);
+    ///
+    /// let mut dsp = Dsp::from_coefficients(coefficients);
+    /// for (i, expected) in expected.into_iter().enumerate() {
+    ///     let byte = frame[1 + i / 2];
+    ///     let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0xF };
+    ///     assert_eq!(dsp.decode_sample(1 << 3, 1, nibble), expected);
+    /// }
+    /// ```
+    pub fn decode_sample(&mut self, scale: i32, coef_index: usize, nibble: u8) -> i16 {
+        let scale = i64::from(scale.clamp(0, 1 << 15));
+        let [coef1, coef2] = self.coefficient_pair(coef_index).map(i64::from);
+        let sample = self.decode_unclamped(scale, coef1, coef2, 1024, get_low_nibble(nibble));
+        self.clamp_sample(sample)
+    }
+
+    /// Decode a run of samples that all use the scale and coefficients of `header`.
+    ///
+    /// Every byte of `nibbles` is one sample, only the lower four bits are used. This is
+    /// useful for formats that refer to samples by nibble offset instead of by frame.
+    /// The history is updated, just like when decoding a frame.
+    ///
+    /// ```
+    /// use gc_adpcm::Dsp;
+    ///
+    /// let coefficients = [1024, -512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    /// let frame = [0x02, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+    /// let expected = Dsp::from_coefficients(coefficients).decode_frame(frame);
+    ///
+    /// let mut dsp = Dsp::from_coefficients(coefficients);
+    /// let first = dsp.decode_nibbles(frame[0], &[0x1, 0x2, 0x3]);
+    /// let rest = dsp.decode_nibbles(frame[0], &[0x4, 0x5, 0x6, 0x7, 0x8, 0x9, 0xA, 0xB, 0xC, 0xD, 0xE]);
+    /// assert_eq!([first, rest].concat(), expected);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn decode_nibbles(&mut self, header: u8, nibbles: &[u8]) -> Vec<i16> {
+        let (scale, coef1, coef2) = self.parameters(header);
+        nibbles
+            .iter()
+            .map(|&nibble| {
+                let sample = get_low_nibble(nibble);
+                let sample = self.decode_unclamped(scale, coef1, coef2, 1024, sample);
+                self.clamp_sample(sample)
+            })
+            .collect()
+    }
+
+    /// Decode the data of a single frame without clamping, using `header` and `rounding`.
+    fn decode(&mut self, header: u8, data: [u8; FRAME_SIZE - 1], rounding: Rounding) -> [i32; 14] {
+        let (scale, coef1, coef2) = self.parameters(header);
+        let rounding = match rounding {
+            Rounding::Nearest => 1024,
+            Rounding::Truncate => 0,
+        };
 
         let mut out = [0; 14];
         let mut i = 0;
 
         // 7 data bytes per frame
-        for byte in &frame[1..] {
-            let byte = *byte;
+        for byte in data {
             // 2 samples per byte
-            for s in 0..2 {
-                let mut sample = if s == 0 {
-                    get_high_nibble(byte)
-                } else {
-                    get_low_nibble(byte)
-                };
-                sample = if sample >= 8 { sample - 16 } else { sample };
-                sample = (((scale * sample) << 11)
-                    + 1024
-                    + (coef1 * i32::from(self.hist1) + coef2 * i32::from(self.hist2)))
-                    >> 11;
-                let sample = clamp(sample);
-
-                out[i] = sample;
+            for sample in self.nibbles(byte) {
+                out[i] = self.decode_unclamped(scale, coef1, coef2, rounding, sample);
                 i += 1;
-
-                self.hist2 = self.hist1;
-                self.hist1 = sample;
             }
         }
         out
     }
+
+    /// Get the two samples in `byte`, in the nibble order of this state.
+    pub(crate) fn nibbles(&self, byte: u8) -> [i32; 2] {
+        match self.nibble_order {
+            NibbleOrder::HighFirst => [get_high_nibble(byte), get_low_nibble(byte)],
+            NibbleOrder::LowFirst => [get_low_nibble(byte), get_high_nibble(byte)],
+        }
+    }
+
+    /// Get the scale and the coefficient pair selected by `header`.
+    ///
+    /// The calculation is done with i64 as the coefficients multiplied with the history
+    /// can overflow an i32.
+    fn parameters(&self, header: u8) -> (i64, i64, i64) {
+        let scale = 1i64 << (header & 0xF);
+        let [coef1, coef2] = self
+            .coefficient_pair(usize::from(header >> 4))
+            .map(i64::from);
+        (scale, coef1, coef2)
+    }
+
+    /// Decode a single sample without clamping and update the history.
+    fn decode_unclamped(
+        &mut self,
+        scale: i64,
+        coef1: i64,
+        coef2: i64,
+        rounding: i64,
+        sample: i32,
+    ) -> i32 {
+        let sample = (((scale * i64::from(sample)) << 11)
+            + rounding
+            + (coef1 * i64::from(self.hist1) + coef2 * i64::from(self.hist2)))
+            >> 11;
+        #[allow(
+            clippy::as_conversions,
+            clippy::cast_possible_truncation,
+            reason = "The calculation above always results in a value that fits in 22 bits."
+        )]
+        let sample = sample as i32;
+
+        self.hist2 = self.hist1;
+        self.hist1 = self.clamp_sample(sample);
+        sample
+    }
+
+    /// Clamp `sample` to the clamp range.
+    fn clamp_sample(&self, sample: i32) -> i16 {
+        clamp(sample.clamp(i32::from(self.clamp_min), i32::from(self.clamp_max)))
+    }
 }
 
-/// The amount of samples in a single frame
-pub const SAMPLES_PER_FRAME: u32 = 14;
-/// The size of one frame in bytes
-pub const FRAME_SIZE: usize = 8;
+/// How the prediction is rounded when decoding a sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest integer, with halves rounded up, like the hardware does
+    #[default]
+    Nearest,
+    /// Round towards negative infinity
+    Truncate,
+}
 
-/// Table to convert a nibble to an [`i32`].
-const NIBBLE_TO_S8: [i32; 0x10] = [0, 1, 2, 3, 4, 5, 6, 7, -8, -7, -6, -5, -4, -3, -2, -1];
+/// Error returned by [`Dsp::decode_frame_slice`] if the frame is not [`FRAME_SIZE`] bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameError {
+    /// The length of the frame
+    pub len: usize,
+}
 
-/// Extract the low nibble from the byte and convert it to a [`i32`].
-fn get_low_nibble(byte: u8) -> i32 {
-    NIBBLE_TO_S8[usize::from(byte & 0xF)]
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a frame is {FRAME_SIZE} bytes, but it is {} bytes",
+            self.len
+        )
+    }
 }
 
-/// Extract the high nibble from the byte and convert it to a [`i32`].
-fn get_high_nibble(byte: u8) -> i32 {
-    NIBBLE_TO_S8[usize::from((byte >> 4) & 0xF)]
+impl std::error::Error for FrameError {}
+
+/// The order of the two samples in a byte of a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NibbleOrder {
+    /// The sample in the high nibble comes first, like the hardware does
+    #[default]
+    HighFirst,
+    /// The sample in the low nibble comes first
+    LowFirst,
 }
 
-/// Clamp an [`i32`] value to [`i16`].
+/// The amount of samples in a single frame
+///
+/// A frame starts with a header byte, followed by 7 bytes with two 4-bit samples each.
+pub const SAMPLES_PER_FRAME: u32 = 14;
+/// The size of one frame in bytes
+///
+/// This includes the header byte, so only 7 bytes contain samples.
+pub const FRAME_SIZE: usize = 8;
+
+/// Calculate the amount of full frames in `bytes` bytes.
+///
+/// Saturates at [`u32::MAX`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::frames_from_bytes(17), 2);
+/// ```
+#[must_use]
 #[allow(
     clippy::as_conversions,
     clippy::cast_possible_truncation,
-    reason = "It's clamped to i16 and therefore safe."
+    reason = "It's checked to fit in u32 and therefore safe."
+)]
+pub const fn frames_from_bytes(bytes: usize) -> u32 {
+    let frames = bytes / FRAME_SIZE;
+    if frames > u32::MAX as usize {
+        u32::MAX
+    } else {
+        frames as u32
+    }
+}
+
+/// Calculate the size in bytes of `frames` frames.
+///
+/// Saturates at [`usize::MAX`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::bytes_from_frames(2), 16);
+/// ```
+#[must_use]
+#[allow(
+    clippy::as_conversions,
+    reason = "u32 fits in usize on all supported platforms."
 )]
-fn clamp(val: i32) -> i16 {
-    val.clamp(-32768, 32767) as i16
+pub const fn bytes_from_frames(frames: u32) -> usize {
+    (frames as usize).saturating_mul(FRAME_SIZE)
+}
+
+/// Calculate the amount of samples in `frames` frames.
+///
+/// Saturates at [`u32::MAX`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::samples_from_frames(2), 28);
+/// ```
+#[must_use]
+pub const fn samples_from_frames(frames: u32) -> u32 {
+    frames.saturating_mul(SAMPLES_PER_FRAME)
+}
+
+/// Calculate the duration in seconds of `frames` frames at `sample_rate` samples per second.
+///
+/// For stereo audio streams `frames` is the amount of frames in *one* channel, as the
+/// channels are played at the same time.
+///
+/// ```
+/// assert_eq!(gc_adpcm::duration_secs(16_000, 32_000), 7.0);
+/// ```
+#[must_use]
+pub fn duration_secs(frames: u32, sample_rate: u32) -> f64 {
+    f64::from(frames) * f64::from(SAMPLES_PER_FRAME) / f64::from(sample_rate)
+}
+
+/// Calculate the amount of samples in `nibbles` nibbles, as stored in the header of a DSP file.
+///
+/// Every frame has 16 nibbles, of which the first two are the header. The amount of
+/// samples is therefore `nibbles / 16 * 14`, plus `nibbles % 16 - 2` if the last frame
+/// is partial and contains more than the header.
+///
+/// ```
+/// assert_eq!(gc_adpcm::samples_from_nibbles(32), 28);
+/// assert_eq!(gc_adpcm::samples_from_nibbles(34), 28);
+/// assert_eq!(gc_adpcm::samples_from_nibbles(37), 31);
+/// ```
+#[must_use]
+pub const fn samples_from_nibbles(nibbles: u32) -> u32 {
+    let partial = (nibbles % 16).saturating_sub(2);
+    nibbles / 16 * SAMPLES_PER_FRAME + partial
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests for [`Dsp`].
+    use super::*;
+
+    /// The states with the most extreme coefficients and history.
+    const EXTREMES: [(i16, i16); 3] = [
+        (i16::MIN, i16::MIN),
+        (i16::MIN, i16::MAX),
+        (i16::MAX, i16::MAX),
+    ];
+
+    /// The most extreme inputs do not overflow.
+    #[test]
+    fn decode_frame_extremes() {
+        for header in 0..=u8::MAX {
+            for data in [0x00, 0x77, 0x88, 0xFF] {
+                for (coefficient, history) in EXTREMES {
+                    let mut dsp = Dsp::new([coefficient; 16], history, history);
+                    dsp.decode_frame([header, data, data, data, data, data, data, data]);
+                }
+            }
+        }
+    }
+
+    /// The unclamped samples fit in 22 bits.
+    #[test]
+    fn decode_frame_raw_range() {
+        for header in 0..=u8::MAX {
+            for data in [0x77, 0x88] {
+                for (coefficient, history) in EXTREMES {
+                    let mut dsp = Dsp::new([coefficient; 16], history, history);
+                    let samples =
+                        dsp.decode_frame_raw([header, data, data, data, data, data, data, data]);
+                    assert!(
+                        samples
+                            .iter()
+                            .all(|sample| (-1_310_688..=1_277_952).contains(sample)),
+                        "Sample out of range for header {header:#04x}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Clamping to the full range is the default.
+    #[test]
+    fn with_clamp_full_range() {
+        let coefficients = [4096, -2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let frame = [0x0C, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77];
+        let full = Dsp::from_coefficients(coefficients)
+            .with_clamp(i16::MIN, i16::MAX)
+            .decode_frame(frame);
+        let default = Dsp::from_coefficients(coefficients).decode_frame(frame);
+        assert_eq!(full, default, "Full range differs from the default");
+    }
+
+    /// Deserializing checks the clamp range.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_clamp_range() {
+        use serde::de::value::{Error, MapDeserializer};
+        use serde::Deserialize;
+
+        let fields = [("clamp_min", -100), ("clamp_max", 100)];
+        let dsp = Dsp::deserialize(MapDeserializer::<_, Error>::new(fields.into_iter()));
+        assert_eq!(
+            dsp.map(|dsp| dsp.clamp_range()),
+            Ok((-100, 100)),
+            "Valid clamp range was not deserialized"
+        );
+
+        let fields = [("clamp_min", 100), ("clamp_max", -100)];
+        let dsp = Dsp::deserialize(MapDeserializer::<_, Error>::new(fields.into_iter()));
+        assert!(dsp.is_err(), "Invalid clamp range was accepted");
+    }
 }
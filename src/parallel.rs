@@ -0,0 +1,50 @@
+//! Decode the channels of a stereo audio stream in parallel using `rayon`.
+use crate::{Decoder, Dsp};
+use std::io::Read;
+
+/// Decode a stereo audio stream where each channel has their own buffer, decoding both
+/// channels in parallel.
+///
+/// `channel_frames` is the amount of frames in *one* channel. The output is identical to
+/// collecting [`Decoder::stereo`], the samples are interleaved starting with the left channel.
+///
+/// ```
+/// use gc_adpcm::{decode_stereo_parallel, Decoder, Dsp};
+/// use std::io::Cursor;
+///
+/// let left = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(4);
+/// let right = [0x13, 0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32].repeat(4);
+/// let state = Dsp::from_coefficients([1024, -512, 2048, -1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+/// let parallel = decode_stereo_parallel(Cursor::new(&left), state, Cursor::new(&right), state, 4)?;
+/// let sequential = Decoder::stereo(Cursor::new(&left), state, Cursor::new(&right), state, 4)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(parallel, sequential);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// # Errors
+/// Returns an error if one of the readers fails.
+pub fn decode_stereo_parallel<R: Read + Send>(
+    left_reader: R,
+    left_state: Dsp,
+    right_reader: R,
+    right_state: Dsp,
+    channel_frames: u32,
+) -> Result<Vec<i16>, std::io::Error> {
+    let (left, right) = rayon::join(
+        || decode_channel(left_reader, left_state, channel_frames),
+        || decode_channel(right_reader, right_state, channel_frames),
+    );
+    let (left, right) = (left?, right?);
+    let mut samples = Vec::with_capacity(left.len() * 2);
+    for (left, right) in left.into_iter().zip(right) {
+        samples.push(left);
+        samples.push(right);
+    }
+    Ok(samples)
+}
+
+/// Decode all frames of a single channel.
+fn decode_channel<R: Read>(reader: R, state: Dsp, frames: u32) -> Result<Vec<i16>, std::io::Error> {
+    Decoder::mono(reader, state, frames).collect()
+}
@@ -0,0 +1,169 @@
+//! A decoder that decodes audio that is already in memory.
+use crate::{Dsp, FRAME_SIZE};
+use core::iter::FusedIterator;
+use core::slice::ChunksExact;
+
+/// The frames of the audio stream(s) for every channel layout.
+enum Frames<'a> {
+    /// One channel
+    Mono(ChunksExact<'a, u8>),
+    /// Two channels in two separate streams
+    Stereo(ChunksExact<'a, u8>, ChunksExact<'a, u8>),
+    /// Two channels interleaved per frame in one stream
+    Interleaved(ChunksExact<'a, u8>),
+}
+
+/// Decoder for audio streams that are completely in memory.
+///
+/// As the length of the stream is known, decoding can not fail and the output is `i16`
+/// instead of `Result<i16, std::io::Error>`. The amount of frames is calculated from the
+/// length of the slice(s), trailing bytes that do not make up a full frame are ignored.
+/// Nothing is allocated, so this decoder is also available without the `std` feature.
+///
+/// The samples of stereo audio are interleaved per sample, starting with the left channel.
+///
+/// ```
+/// use gc_adpcm::{Dsp, SliceDecoder};
+///
+/// let frames = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE].repeat(2);
+/// let mono = SliceDecoder::mono(&frames, Dsp::default());
+/// assert_eq!(mono.len(), 28);
+/// let stereo = SliceDecoder::stereo(&frames, Dsp::default(), &frames, Dsp::default());
+/// assert_eq!(stereo.len(), 56);
+/// let interleaved = SliceDecoder::interleaved_stereo(&frames, Dsp::default(), Dsp::default());
+/// assert_eq!(interleaved.len(), 28);
+///
+/// let decoder = gc_adpcm::Decoder::stereo(&frames[..], Dsp::default(), &frames[..], Dsp::default(), 2);
+/// assert!(decoder.map(Result::unwrap).eq(stereo));
+/// ```
+pub struct SliceDecoder<'a> {
+    /// The frames that still need to be decoded
+    frames: Frames<'a>,
+    /// The DSP state of the left/mono channel
+    left_state: Dsp,
+    /// The DSP state of the right channel, unused for mono
+    right_state: Dsp,
+    /// The amount of channels in the output
+    channels: usize,
+    /// The decoded frame of every channel
+    decoded: [[i16; 14]; 2],
+    /// The index of the next sample in the interleaved output of `decoded`
+    position: usize,
+}
+
+impl<'a> SliceDecoder<'a> {
+    /// Decode a mono audio stream.
+    #[must_use]
+    pub fn mono(data: &'a [u8], state: Dsp) -> Self {
+        Self::new(
+            Frames::Mono(data.chunks_exact(FRAME_SIZE)),
+            state,
+            Dsp::default(),
+            1,
+        )
+    }
+
+    /// Decode a stereo audio stream where each channel has their own buffer.
+    ///
+    /// If the slices have a different length, the shortest length is used.
+    #[must_use]
+    pub fn stereo(left: &'a [u8], left_state: Dsp, right: &'a [u8], right_state: Dsp) -> Self {
+        let len = left.len().min(right.len());
+        let frames = Frames::Stereo(
+            left[..len].chunks_exact(FRAME_SIZE),
+            right[..len].chunks_exact(FRAME_SIZE),
+        );
+        Self::new(frames, left_state, right_state, 2)
+    }
+
+    /// Decode a stereo audio stream interleaved per frame.
+    #[must_use]
+    pub fn interleaved_stereo(data: &'a [u8], left_state: Dsp, right_state: Dsp) -> Self {
+        let frames = Frames::Interleaved(data.chunks_exact(FRAME_SIZE * 2));
+        Self::new(frames, left_state, right_state, 2)
+    }
+
+    /// Create the decoder with nothing decoded yet.
+    const fn new(frames: Frames<'a>, left_state: Dsp, right_state: Dsp, channels: usize) -> Self {
+        Self {
+            frames,
+            left_state,
+            right_state,
+            channels,
+            decoded: [[0; 14]; 2],
+            position: 14 * channels,
+        }
+    }
+
+    /// Decode the next frame of every channel.
+    ///
+    /// Returns `false` if there are no frames left.
+    fn decode_next_frames(&mut self) -> bool {
+        match &mut self.frames {
+            Frames::Mono(frames) => {
+                let Some(frame) = frames.next() else {
+                    return false;
+                };
+                self.decoded[0] = self.left_state.decode_frame(to_frame(frame));
+            }
+            Frames::Stereo(left, right) => {
+                let (Some(left), Some(right)) = (left.next(), right.next()) else {
+                    return false;
+                };
+                self.decoded = [
+                    self.left_state.decode_frame(to_frame(left)),
+                    self.right_state.decode_frame(to_frame(right)),
+                ];
+            }
+            Frames::Interleaved(frames) => {
+                let Some(frames) = frames.next() else {
+                    return false;
+                };
+                let (left, right) = frames.split_at(FRAME_SIZE);
+                self.decoded = [
+                    self.left_state.decode_frame(to_frame(left)),
+                    self.right_state.decode_frame(to_frame(right)),
+                ];
+            }
+        }
+        self.position = 0;
+        true
+    }
+
+    /// The amount of frames per channel that still need to be decoded.
+    fn frames_remaining(&self) -> usize {
+        match &self.frames {
+            Frames::Mono(frames) | Frames::Stereo(frames, _) | Frames::Interleaved(frames) => {
+                frames.len()
+            }
+        }
+    }
+}
+
+impl Iterator for SliceDecoder<'_> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == 14 * self.channels && !self.decode_next_frames() {
+            return None;
+        }
+        let sample = self.decoded[self.position % self.channels][self.position / self.channels];
+        self.position += 1;
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining =
+            self.frames_remaining() * 14 * self.channels + 14 * self.channels - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for SliceDecoder<'_> {}
+
+impl FusedIterator for SliceDecoder<'_> {}
+
+/// Convert a chunk of [`FRAME_SIZE`] bytes to a frame.
+fn to_frame(chunk: &[u8]) -> [u8; FRAME_SIZE] {
+    chunk.try_into().unwrap_or_else(|_| unreachable!())
+}
@@ -0,0 +1,30 @@
+//! Fixtures shared by the unit tests.
+use std::io::Read;
+
+/// A frame with every nibble value, using coefficient pair 0 and a scale of 1.
+pub const FRAME: [u8; crate::FRAME_SIZE] = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE];
+
+/// Stream data of `count` copies of [`FRAME`].
+pub fn frames(count: usize) -> Vec<u8> {
+    FRAME.repeat(count)
+}
+
+/// A reader that always fails.
+pub struct Broken;
+
+impl Read for Broken {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("broken"))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for Broken {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        _buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Err(std::io::Error::other("broken")))
+    }
+}
@@ -0,0 +1,51 @@
+//! Small helpers for decoding ADPCM samples.
+//!
+//! These are the building blocks used by [`Dsp`](crate::Dsp), and can be reused for
+//! decoding similar ADPCM formats.
+
+/// Table to convert a 4-bit two's complement nibble to an [`i32`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::util::NIBBLE_TO_S8[0x7], 7);
+/// assert_eq!(gc_adpcm::util::NIBBLE_TO_S8[0x8], -8);
+/// ```
+pub const NIBBLE_TO_S8: [i32; 0x10] = [0, 1, 2, 3, 4, 5, 6, 7, -8, -7, -6, -5, -4, -3, -2, -1];
+
+/// Extract the low nibble from the byte and convert it to a [`i32`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::util::get_low_nibble(0x1F), -1);
+/// ```
+#[must_use]
+pub fn get_low_nibble(byte: u8) -> i32 {
+    NIBBLE_TO_S8[usize::from(byte & 0xF)]
+}
+
+/// Extract the high nibble from the byte and convert it to a [`i32`].
+///
+/// ```
+/// assert_eq!(gc_adpcm::util::get_high_nibble(0x1F), 1);
+/// ```
+#[must_use]
+pub fn get_high_nibble(byte: u8) -> i32 {
+    NIBBLE_TO_S8[usize::from((byte >> 4) & 0xF)]
+}
+
+/// Clamp an [`i32`] value to [`i16`].
+///
+/// ```
+/// use gc_adpcm::util::clamp;
+///
+/// assert_eq!(clamp(40_000), i16::MAX);
+/// assert_eq!(clamp(-40_000), i16::MIN);
+/// assert_eq!(clamp(1234), 1234);
+/// ```
+#[must_use]
+#[allow(
+    clippy::as_conversions,
+    clippy::cast_possible_truncation,
+    reason = "It's clamped to i16 and therefore safe."
+)]
+pub fn clamp(val: i32) -> i16 {
+    val.clamp(-32768, 32767) as i16
+}
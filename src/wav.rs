@@ -0,0 +1,209 @@
+//! Write the output of a [`Decoder`] to a WAV file using `hound`.
+use crate::{Channels, Decoder, DspHeader};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Write all samples of `decoder` to `writer` as a WAV file.
+///
+/// The amount of channels is determined by the channel layout of the decoder.
+///
+/// # Errors
+/// Returns an error if decoding fails or if writing the WAV file fails.
+pub fn write_wav<R, C, W>(
+    decoder: Decoder<R, C>,
+    sample_rate: u32,
+    writer: W,
+) -> Result<(), hound::Error>
+where
+    R: Read,
+    C: Channels,
+    W: Write + Seek,
+    Decoder<R, C>: Iterator<Item = Result<i16, std::io::Error>>,
+{
+    let spec = hound::WavSpec {
+        channels: u16::try_from(C::CHANNEL_COUNT).unwrap_or_else(|_| unreachable!()),
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = hound::WavWriter::new(writer, spec)?;
+    for sample in decoder {
+        wav.write_sample(sample?)?;
+    }
+    wav.finalize()
+}
+
+/// Convert DSP files to WAV files in `output_dir`.
+///
+/// Every file is decoded with the information in its [`DspHeader`], and written to a
+/// WAV file with the same name, where the last extension is replaced by `.wav`. If
+/// `sample_rate` is `None`, the sample rate in the header is used. With the `rayon` feature
+/// the files are converted in parallel.
+///
+/// A file that fails to convert does not stop the other files from being converted, and
+/// its partially written WAV file is removed. If multiple files would be written to the
+/// same WAV file, only the first one is converted and the others return an error. The
+/// result of every file is returned in the same order as `paths`.
+///
+/// ```no_run
+/// use gc_adpcm::convert_files;
+/// use std::path::{Path, PathBuf};
+///
+/// let paths = [PathBuf::from("music.dsp"), PathBuf::from("jingle.dsp")];
+/// for (path, result) in paths.iter().zip(convert_files(&paths, None, Path::new("wav"))) {
+///     if let Err(error) = result {
+///         eprintln!("Failed to convert {}: {error}", path.display());
+///     }
+/// }
+/// ```
+#[must_use]
+pub fn convert_files(
+    paths: &[PathBuf],
+    sample_rate: Option<u32>,
+    output_dir: &Path,
+) -> Vec<Result<(), hound::Error>> {
+    // Decide the outputs up front, so the first file keeps its output regardless of the
+    // order in which the files are converted
+    let mut outputs = HashSet::new();
+    let jobs: Vec<_> = paths
+        .iter()
+        .map(|path| {
+            let output = output_path(path, output_dir);
+            let unique = outputs.insert(output.clone());
+            (path, output, unique)
+        })
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        jobs.par_iter()
+            .map(|(path, output, unique)| convert_file(path, sample_rate, output, *unique))
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        jobs.iter()
+            .map(|(path, output, unique)| convert_file(path, sample_rate, output, *unique))
+            .collect()
+    }
+}
+
+/// The path of the WAV file in `output_dir` for the DSP file at `path`.
+fn output_path(path: &Path, output_dir: &Path) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or(path.as_os_str()).to_owned();
+    name.push(".wav");
+    output_dir.join(name)
+}
+
+/// Convert a DSP file to the WAV file `output`.
+///
+/// If `unique` is false, another file is already converted to `output` and an error is
+/// returned instead.
+fn convert_file(
+    path: &Path,
+    sample_rate: Option<u32>,
+    output: &Path,
+    unique: bool,
+) -> Result<(), hound::Error> {
+    if !unique {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} is also the output of another file", output.display()),
+        )
+        .into());
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut bytes = [0; DspHeader::SIZE];
+    reader.read_exact(&mut bytes)?;
+    let header = DspHeader::parse(&bytes)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let decoder = Decoder::mono_samples(reader, header.dsp(), header.sample_count);
+
+    let writer = BufWriter::new(File::create(output)?);
+    let result = write_wav(decoder, sample_rate.unwrap_or(header.sample_rate), writer);
+    if result.is_err() {
+        // Don't leave a truncated WAV file behind. The original error is more useful than
+        // an error from removing the file.
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests for converting files.
+    use super::*;
+    use crate::test_util::FRAME;
+
+    /// Failing files are reported without stopping the others, and their output is removed.
+    #[test]
+    fn convert_files_errors() -> Result<(), std::io::Error> {
+        let header = DspHeader {
+            sample_count: 14,
+            nibble_count: 16,
+            sample_rate: 32_000,
+            looping: false,
+            format: 0,
+            loop_start: 2,
+            loop_end: 15,
+            current_address: 2,
+            coefficients: [0; 16],
+            gain: 0,
+            predictor_scale: 0,
+            hist1: 0,
+            hist2: 0,
+            loop_predictor_scale: 0,
+            loop_hist1: 0,
+            loop_hist2: 0,
+        };
+        let dir = std::env::temp_dir().join("gc_adpcm_convert_files");
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("sound.dsp");
+        std::fs::write(&file, [&header.to_bytes()[..], &FRAME].concat())?;
+
+        // Only the last extension is replaced
+        let versioned = dir.join("sound.v2.dsp");
+        std::fs::copy(&file, &versioned)?;
+        // Would overwrite the output of `sound.dsp`
+        std::fs::create_dir_all(dir.join("other"))?;
+        let duplicate = dir.join("other").join("sound.dsp");
+        std::fs::copy(&file, &duplicate)?;
+        // Ends halfway through the first frame
+        let truncated = dir.join("truncated.dsp");
+        std::fs::write(&truncated, [&header.to_bytes()[..], &FRAME[..2]].concat())?;
+
+        let paths = [
+            file,
+            dir.join("missing.dsp"),
+            versioned,
+            duplicate,
+            truncated,
+        ];
+        let results = convert_files(&paths, None, &dir);
+        let succeeded: Vec<bool> = results.iter().map(Result::is_ok).collect();
+        assert_eq!(
+            succeeded,
+            [true, false, true, false, false],
+            "Wrong files were converted"
+        );
+        let wav = hound::WavReader::open(dir.join("sound.wav"))
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        assert_eq!(
+            (wav.spec().sample_rate, wav.len()),
+            (32_000, 14),
+            "Wrong WAV file"
+        );
+        assert!(
+            dir.join("sound.v2.wav").exists(),
+            "Versioned file is missing"
+        );
+        assert!(
+            !dir.join("truncated.wav").exists(),
+            "Output of the failed file was not removed"
+        );
+        Ok(())
+    }
+}